@@ -5,7 +5,12 @@ struct VertexOutput {
 }
 
 struct Dim {
-    rate: f32
+    rate: f32,
+    amplitude: f32,
+    frequency: f32,
+    phase: f32,
+    aspect_ratio: f32,
+    direction: u32,
 }
 
 @group(1) @binding(0)
@@ -17,13 +22,31 @@ var s: sampler;
 @group(3) @binding(0)
 var<uniform> dim: Dim;
 
-fn SineWave( p: vec2<f32>) -> vec2<f32> {
+fn SineWave(p: vec2<f32>) -> vec2<f32> {
     let pi = 3.14159;
-    let A = 0.30;
-    let w = 10.0 * pi;
-    let t = 30.0 * pi / 180.0;
-    let y = sin( w*p.x + t) * A * dim.rate;
-    return vec2(p.x, p.y + y);
+    let w = 2.0 * dim.frequency * pi;
+    let phase = dim.phase;
+    let A = dim.amplitude * dim.rate;
+
+    if dim.direction == 1u {
+        // vertical
+        return vec2(p.x + sin(w * p.y + phase) * A, p.y);
+    }
+
+    if dim.direction == 2u {
+        // radial, the distance is computed in a square space, so the ripples are circles
+        let center = vec2(0.5, 0.5);
+        let delta = (p - center) * vec2(dim.aspect_ratio, 1.0);
+        let distance = length(delta);
+        if distance == 0.0 {
+            return p;
+        }
+        let displacement = sin(w * distance + phase) * A;
+        return p + normalize(delta) * displacement / vec2(dim.aspect_ratio, 1.0);
+    }
+
+    // horizontal
+    return vec2(p.x, p.y + sin(w * p.x + phase) * A);
 }
 
 @fragment
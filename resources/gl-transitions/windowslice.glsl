@@ -0,0 +1,11 @@
+// Author: gre
+// License: MIT
+
+uniform float count; // = 10.0
+uniform float smoothness; // = 0.5
+
+vec4 transition (vec2 p) {
+  float pr = smoothstep(-smoothness, 0.0, p.x - progress * (1.0 + smoothness));
+  float s = step(pr, fract(count * p.x));
+  return mix(getFromColor(p), getToColor(p), s);
+}
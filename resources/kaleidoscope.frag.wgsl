@@ -0,0 +1,57 @@
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+}
+
+struct Dim {
+    progress: f32,
+    // the number of mirrored slices
+    segments: f32,
+    // the turns of the kaleidoscope during the transition
+    rotation: f32,
+    aspectRatio: f32,
+}
+
+const PI: f32 = 3.14159265;
+
+// the new image
+@group(1) @binding(0)
+var t: texture_2d<f32>;
+
+@group(1) @binding(1)
+var s: sampler;
+
+@group(3) @binding(0)
+var<uniform> dim: Dim;
+
+// the old image
+@group(3) @binding(1)
+var old_image: texture_2d<f32>;
+
+@group(3) @binding(2)
+var old_sampler: sampler;
+
+// Folds the point in the first slice, mirroring every other slice, so the image repeats around
+// the center like in a kaleidoscope.
+fn fold(p: vec2<f32>) -> vec2<f32> {
+    let segment = 2.0 * PI / max(dim.segments, 1.0);
+    let angle = atan2(p.y, p.x) + dim.rotation * 2.0 * PI * dim.progress;
+    let folded = abs(angle - segment * floor(angle / segment) - segment / 2.0);
+    return vec2(cos(folded), sin(folded)) * length(p);
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let scale = vec2(dim.aspectRatio, 1.0);
+    let p = (in.uv - vec2(0.5, 0.5)) * scale;
+
+    // the kaleidoscope appears and disappears, it's complete in the middle of the transition
+    let strength = sin(PI * dim.progress);
+    let uv = mix(in.uv, fold(p) / scale + vec2(0.5, 0.5), strength);
+
+    let oldColor = textureSampleLevel(old_image, old_sampler, uv, 0.0);
+    let newColor = textureSampleLevel(t, s, uv, 0.0) * in.color;
+
+    return mix(oldColor, newColor, smoothstep(0.4, 0.6, dim.progress));
+}
@@ -0,0 +1,43 @@
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+}
+
+struct Dim {
+    progress: f32,
+    softness: f32,
+    invert: u32,
+}
+
+@group(1) @binding(0)
+var t: texture_2d<f32>;
+
+@group(1) @binding(1)
+var s: sampler;
+
+@group(3) @binding(0)
+var<uniform> dim: Dim;
+
+// a grayscale image, stretched to the size of the image
+@group(3) @binding(1)
+var mask: texture_2d<f32>;
+
+@group(3) @binding(2)
+var mask_sampler: sampler;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(t, s, in.uv) * in.color;
+
+    var luma = textureSample(mask, mask_sampler, in.uv).r;
+    if dim.invert != 0u {
+        luma = 1.0 - luma;
+    }
+
+    // the progress is extended by the softness, so at the end even the brightest areas are opaque
+    let progress = dim.progress * (1.0 + dim.softness);
+    let alpha = clamp((progress - luma) / max(dim.softness, 0.0001), 0.0, 1.0);
+
+    return vec4(color.rgb, color.a * alpha);
+}
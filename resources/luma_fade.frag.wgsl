@@ -0,0 +1,46 @@
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+}
+
+struct Dim {
+    progress: f32,
+    softness: f32,
+    invert: u32,
+}
+
+// the new image
+@group(1) @binding(0)
+var t: texture_2d<f32>;
+
+@group(1) @binding(1)
+var s: sampler;
+
+@group(3) @binding(0)
+var<uniform> dim: Dim;
+
+// the old image, its luminance decides when each pixel changes
+@group(3) @binding(1)
+var old_image: texture_2d<f32>;
+
+@group(3) @binding(2)
+var old_sampler: sampler;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let newColor = textureSample(t, s, in.uv) * in.color;
+    let oldColor = textureSample(old_image, old_sampler, in.uv);
+
+    // bright areas first, so they change when the threshold is low
+    var threshold = 1.0 - dot(oldColor.rgb, vec3(0.2126, 0.7152, 0.0722));
+    if dim.invert != 0u {
+        threshold = 1.0 - threshold;
+    }
+
+    // the progress is extended by the softness, so at the end even the last areas are changed
+    let progress = dim.progress * (1.0 + dim.softness);
+    let alpha = clamp((progress - threshold) / max(dim.softness, 0.0001), 0.0, 1.0);
+
+    return mix(oldColor, newColor, alpha);
+}
@@ -0,0 +1,101 @@
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+}
+
+struct Dim {
+    progress: f32,
+    radius: f32,
+    aspectRatio: f32,
+    // the distance the curl travels, from the corner to the farthest point of the page
+    length: f32,
+    // the corner the page curls from, in the space where the height of the image is 1
+    corner: vec2<f32>,
+    // the direction the curl travels, a unit vector
+    direction: vec2<f32>,
+}
+
+const PI: f32 = 3.14159265;
+
+// the new image
+@group(1) @binding(0)
+var t: texture_2d<f32>;
+
+@group(1) @binding(1)
+var s: sampler;
+
+@group(3) @binding(0)
+var<uniform> dim: Dim;
+
+// the old image, the page that curls
+@group(3) @binding(1)
+var page: texture_2d<f32>;
+
+@group(3) @binding(2)
+var page_sampler: sampler;
+
+fn inPage(p: vec2<f32>) -> bool {
+    return p.x >= 0.0 && p.x <= dim.aspectRatio && p.y >= 0.0 && p.y <= 1.0;
+}
+
+fn pageColor(p: vec2<f32>) -> vec4<f32> {
+    return textureSampleLevel(page, page_sampler, p / vec2(dim.aspectRatio, 1.0), 0.0);
+}
+
+// The back of the page is a washed out version of the front.
+fn backColor(p: vec2<f32>, light: f32) -> vec4<f32> {
+    let color = pageColor(p);
+    let gray = dot(color.rgb, vec3(0.299, 0.587, 0.114));
+    return vec4(mix(vec3(gray), vec3(1.0), 0.6) * light, 1.0);
+}
+
+// The page is flat from the axis of the curl on, before the axis it's rolled on a cylinder of
+// dim.radius, and after half a turn it's flat again, over the page.
+// x is the distance from the corner along the direction, s is the same distance for the point of
+// the page before it curled.
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let p = in.uv * vec2(dim.aspectRatio, 1.0);
+    let r = dim.radius;
+    let axis = dim.progress * (dim.length + r);
+    let x = dot(p - dim.corner, dim.direction);
+
+    if x >= axis {
+        // the back of the page, flat over the page
+        let backS = 2.0 * axis - PI * r - x;
+        let back = p - dim.direction * (x - backS);
+        if backS >= 0.0 && inPage(back) {
+            return backColor(back, 0.9);
+        }
+
+        // the page not yet curled, with the shadow of the curl, that grows with it
+        let curl = min(axis / max(r, 0.0001), 1.0);
+        let shadow = 1.0 - 0.3 * exp(-(x - axis) / max(r, 0.0001) * 4.0) * curl;
+        return vec4(pageColor(p).rgb * shadow, 1.0);
+    }
+
+    if x >= axis - r {
+        let angle = asin(clamp((axis - x) / r, 0.0, 1.0));
+
+        // the back of the cylinder is above the front
+        let backS = axis - (PI - angle) * r;
+        let back = p - dim.direction * (x - backS);
+        if backS >= 0.0 && inPage(back) {
+            return backColor(back, 0.7 + 0.3 * sin(angle));
+        }
+
+        let frontS = axis - angle * r;
+        let front = p - dim.direction * (x - frontS);
+        if inPage(front) {
+            // the light comes from the front, the curl gets darker while it turns
+            return vec4(pageColor(front).rgb * (0.6 + 0.4 * cos(angle)), 1.0);
+        }
+    }
+
+    // the new image, with the shadow of the curl
+    let distance = max(axis - r - x, 0.0);
+    let shadow = 1.0 - 0.5 * exp(-distance / max(r, 0.0001) * 3.0) * (1.0 - dim.progress);
+    let color = textureSampleLevel(t, s, in.uv, 0.0) * in.color;
+    return vec4(color.rgb * shadow, color.a);
+}
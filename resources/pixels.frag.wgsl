@@ -0,0 +1,42 @@
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+}
+
+struct Dim {
+    progress: f32,
+    block_size: f32,
+    width: f32,
+    height: f32,
+}
+
+@group(1) @binding(0)
+var t: texture_2d<f32>;
+
+@group(1) @binding(1)
+var s: sampler;
+
+@group(3) @binding(0)
+var<uniform> dim: Dim;
+
+// one texel per block
+@group(3) @binding(1)
+var thresholds: texture_2d<f32>;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(t, s, in.uv) * in.color;
+
+    let size = vec2<i32>(textureDimensions(thresholds));
+    let block = vec2<i32>(in.uv * vec2(dim.width, dim.height) / dim.block_size);
+    let packed = textureLoad(thresholds, clamp(block, vec2(0, 0), size - 1), 0);
+    // the threshold is stored in 16 bits, the most significant in the red channel
+    let threshold = (packed.r * 255.0 * 256.0 + packed.g * 255.0) / 65535.0;
+
+    if threshold >= dim.progress {
+        discard;
+    }
+
+    return color;
+}
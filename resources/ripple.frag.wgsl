@@ -0,0 +1,66 @@
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+}
+
+struct Dim {
+    progress: f32,
+    // the maximum displacement, relative to the height of the image
+    amplitude: f32,
+    // the number of waves per height of the image
+    frequency: f32,
+    aspectRatio: f32,
+    // where the drop falls
+    center: vec2<f32>,
+}
+
+const PI: f32 = 3.14159265;
+
+// the new image
+@group(1) @binding(0)
+var t: texture_2d<f32>;
+
+@group(1) @binding(1)
+var s: sampler;
+
+@group(3) @binding(0)
+var<uniform> dim: Dim;
+
+// the old image
+@group(3) @binding(1)
+var old_image: texture_2d<f32>;
+
+@group(3) @binding(2)
+var old_sampler: sampler;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // in the space where the height is 1, so the waves are circles
+    let scale = vec2(dim.aspectRatio, 1.0);
+    let delta = (in.uv - dim.center) * scale;
+    let distance = length(delta);
+
+    // the farthest corner from the drop
+    let corners = max(abs(dim.center), abs(vec2(1.0, 1.0) - dim.center)) * scale;
+    let maxDistance = length(corners);
+
+    // the front of the wave, the new image is inside it
+    let front = dim.progress * (maxDistance + 0.1);
+    let fromFront = distance - front;
+
+    // the waves are near the front and they calm down at the end
+    let wave = sin(fromFront * dim.frequency * 2.0 * PI) * exp(-abs(fromFront) * 8.0) * (1.0 - dim.progress);
+
+    var direction = vec2(0.0, 0.0);
+    if distance > 0.0 {
+        direction = delta / distance;
+    }
+
+    let uv = in.uv + direction * wave * dim.amplitude / scale;
+
+    let oldColor = textureSampleLevel(old_image, old_sampler, uv, 0.0);
+    let newColor = textureSampleLevel(t, s, uv, 0.0) * in.color;
+
+    return mix(newColor, oldColor, smoothstep(-0.02, 0.02, fromFront));
+}
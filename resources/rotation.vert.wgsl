@@ -0,0 +1,72 @@
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+    // greater than zero when the face is turned toward the camera
+    @location(2) facing: f32,
+}
+
+struct GgezDrawUniforms {
+    color: vec4<f32>,
+    src_rect: vec4<f32>,
+    transform: mat4x4<f32>,
+}
+
+struct Dim {
+    // the rotation of the face, in radians
+    angle: f32,
+    // the distance of the face from the axis of rotation, relative to the height of the image
+    depth: f32,
+    // the distance of the camera from the image, relative to the height of the image
+    distance: f32,
+    aspectRatio: f32,
+    // 0 to rotate around the vertical axis, 1 around the horizontal one
+    axis: u32,
+    // how much the rotating object is moved away from the camera, relative to the height of the image
+    push: f32,
+}
+
+@group(0) @binding(0)
+var<uniform> uniforms: GgezDrawUniforms;
+
+@group(3) @binding(0)
+var<uniform> dim: Dim;
+
+fn rotate(p: vec3<f32>) -> vec3<f32> {
+    let c = cos(dim.angle);
+    let s = sin(dim.angle);
+
+    if dim.axis == 0u {
+        return vec3(p.x * c + p.z * s, p.y, -p.x * s + p.z * c);
+    }
+    return vec3(p.x, p.y * c - p.z * s, p.y * s + p.z * c);
+}
+
+@vertex
+fn vs_main(
+    @location(0) position: vec2<f32>,
+    @location(1) uv: vec2<f32>,
+    @location(2) color: vec4<f32>,
+) -> VertexOutput {
+    var out: VertexOutput;
+
+    // the face, centered, with y up and the height of the image as unit, then rotated around the
+    // axis, that is depth behind it, and moved back to the screen
+    let local = vec3((position.x - 0.5) * dim.aspectRatio, 0.5 - position.y, dim.depth);
+    let offset = vec3(0.0, 0.0, dim.depth + dim.push);
+    let p = rotate(local) - offset;
+    let center = rotate(vec3(0.0, 0.0, dim.depth)) - offset;
+    let normal = rotate(vec3(0.0, 0.0, 1.0));
+
+    // the camera looks at the screen from distance, toward negative z
+    let w = (dim.distance - p.z) / dim.distance;
+    let projected = vec2(p.x / w / dim.aspectRatio + 0.5, 0.5 - p.y / w);
+    let clip = uniforms.transform * vec4(projected, 0.0, 1.0);
+
+    // multiplied by w, so the texture coordinates are interpolated with perspective
+    out.position = vec4(clip.xy * w, clip.z * w, w);
+    out.uv = uv * uniforms.src_rect.zw + uniforms.src_rect.xy;
+    out.color = color * vec4(vec3(mix(0.5, 1.0, max(normal.z, 0.0))), 1.0);
+    out.facing = dot(normal, vec3(0.0, 0.0, dim.distance) - center);
+    return out;
+}
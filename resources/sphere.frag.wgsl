@@ -8,11 +8,12 @@ struct VertexOutput {
 
 struct Dim {
     rate: f32,
-    center_x: f32,
-    center_y: f32,
     radius: f32,
     aspectRatio: f32,
-    refractiveIndex: f32
+    refractiveIndex: f32,
+    count: u32,
+    // x, y: the center relative to the size of the image, z: the radius relative to the shorter side
+    bubbles: array<vec4<f32>, 8>,
 }
 
 @group(1) @binding(0)
@@ -24,26 +25,52 @@ var s: sampler;
 @group(3) @binding(0)
 var<uniform> dim: Dim;
 
+// Maps the texture coordinates to a square space where the shorter side of the image is 1 long,
+// so the bubbles are round for landscape and portrait images.
+fn toSquare(p: vec2<f32>) -> vec2<f32> {
+    if dim.aspectRatio >= 1.0 {
+        return vec2((p.x - 0.5) * dim.aspectRatio + 0.5, p.y);
+    }
+    return vec2(p.x, (p.y - 0.5) / dim.aspectRatio + 0.5);
+}
+
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    //vec2 textureCoordinateToUse = vec2(in.uv.x, (in.uv.y * dim.aspectRatio + 0.5 - 0.5 * dim.aspectRatio));
-    let textureCoordinateToUse = vec2((in.uv.x * dim.aspectRatio + 0.5 - 0.5 * dim.aspectRatio), in.uv.y);
-    let center = vec2(dim.center_x, dim.center_y);
-    let distanceFromCenter = distance(center, textureCoordinateToUse);
-    let real_radius = 0.5;
-    let checkForPresenceWithinSphere = step(distanceFromCenter, real_radius);
+    let textureCoordinateToUse = toSquare(in.uv);
+
+    // the bubbles are metaballs, so they merge when they are near
+    var field = 0.0;
+    var gradient = vec2(0.0, 0.0);
+    for (var i = 0u; i < dim.count; i++) {
+        let bubble = dim.bubbles[i];
+        let delta = textureCoordinateToUse - toSquare(bubble.xy);
+        let distance2 = max(dot(delta, delta), 0.00000001);
+        let radius2 = bubble.z * bubble.z;
+        field += radius2 / distance2;
+        // the opposite of the gradient of the field, it points outward
+        gradient += radius2 * delta / (distance2 * distance2);
+    }
 
-    let distanceFromCenter0 = distanceFromCenter / dim.radius;
+    // the distance from the center relative to the radius, for a single bubble
+    let distanceFromCenter = 1.0 / sqrt(max(field, 0.00000001));
+    let checkForPresenceWithinSphere = step(distanceFromCenter, 1.0);
 
-    let normalizedDepth = dim.radius * sqrt(1.0 - distanceFromCenter0 * distanceFromCenter0);
-    let sphereNormal = normalize(vec3(textureCoordinateToUse - center, normalizedDepth));
+    var outward = vec2(0.0, 0.0);
+    if length(gradient) > 0.0 {
+        outward = normalize(gradient);
+    }
+
+    // the refraction is computed on a sphere of dim.radius, that is bigger than the real one
+    let relativeRadius = dim.radius / 0.5;
+    let distanceFromCenter0 = min(distanceFromCenter / relativeRadius, 1.0);
+
+    let normalizedDepth = relativeRadius * sqrt(1.0 - distanceFromCenter0 * distanceFromCenter0);
+    let sphereNormal = normalize(vec3(outward * distanceFromCenter, normalizedDepth));
 
     let refractedVector = refract(vec3(0.0, 0.0, -1.0), sphereNormal, dim.refractiveIndex);
 
-    // I recalculate normalizedDepth with real_radius to smooth the sphere
-    let distanceFromCenter1 = distance(center, textureCoordinateToUse);
-    let distanceFromCenter2 = distanceFromCenter1 / real_radius;
-    let normalizedDepth0 = sqrt(1.0 - distanceFromCenter2 * distanceFromCenter2);
+    // I recalculate normalizedDepth with the real radius to smooth the sphere
+    let normalizedDepth0 = sqrt(max(1.0 - distanceFromCenter * distanceFromCenter, 0.0));
 
     return textureSample(t, s, (refractedVector.xy + 1.0) * 0.5) * checkForPresenceWithinSphere * normalizedDepth0;
-}
\ No newline at end of file
+}
@@ -0,0 +1,56 @@
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+}
+
+struct Dim {
+    progress: f32,
+    // the rotation at the center, in radians, in the middle of the transition
+    strength: f32,
+    // the radius of the swirl, relative to the height of the image
+    radius: f32,
+    aspectRatio: f32,
+    center: vec2<f32>,
+}
+
+const PI: f32 = 3.14159265;
+
+// the new image
+@group(1) @binding(0)
+var t: texture_2d<f32>;
+
+@group(1) @binding(1)
+var s: sampler;
+
+@group(3) @binding(0)
+var<uniform> dim: Dim;
+
+// the old image
+@group(3) @binding(1)
+var old_image: texture_2d<f32>;
+
+@group(3) @binding(2)
+var old_sampler: sampler;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // in the space where the height is 1, so the swirl is round
+    let scale = vec2(dim.aspectRatio, 1.0);
+    let delta = (in.uv - dim.center) * scale;
+    let distance = length(delta);
+
+    // the images twist more and more until the middle of the transition, then they untwist
+    let falloff = max(1.0 - distance / dim.radius, 0.0);
+    let angle = dim.strength * sin(PI * dim.progress) * falloff * falloff;
+
+    let c = cos(angle);
+    let s0 = sin(angle);
+    let rotated = vec2(delta.x * c - delta.y * s0, delta.x * s0 + delta.y * c);
+    let uv = dim.center + rotated / scale;
+
+    let oldColor = textureSampleLevel(old_image, old_sampler, uv, 0.0);
+    let newColor = textureSampleLevel(t, s, uv, 0.0) * in.color;
+
+    return mix(oldColor, newColor, smoothstep(0.3, 0.7, dim.progress));
+}
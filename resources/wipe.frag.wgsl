@@ -0,0 +1,63 @@
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+}
+
+struct Dim {
+    progress: f32,
+    // 0: circle, 1: diamond, 2: clock
+    shape: u32,
+    // 1 to close the iris, or to turn the clock counterclockwise
+    reverse: u32,
+    aspectRatio: f32,
+}
+
+const PI: f32 = 3.14159265;
+
+@group(1) @binding(0)
+var t: texture_2d<f32>;
+
+@group(1) @binding(1)
+var s: sampler;
+
+@group(3) @binding(0)
+var<uniform> dim: Dim;
+
+// When the pixel is revealed, from 0 to 1: the distance from the center relative to the corners,
+// or the angle from twelve o'clock.
+fn revealAt(uv: vec2<f32>) -> f32 {
+    let scale = vec2(dim.aspectRatio, 1.0);
+    let delta = (uv - vec2(0.5, 0.5)) * scale;
+    let corner = 0.5 * scale;
+
+    if dim.shape == 1u {
+        return (abs(delta.x) + abs(delta.y)) / (corner.x + corner.y);
+    }
+
+    if dim.shape == 2u {
+        // clockwise, y goes down
+        let angle = atan2(delta.x, -delta.y) / (2.0 * PI);
+        return fract(angle + 1.0);
+    }
+
+    return length(delta) / length(corner);
+}
+
+// The new image is drawn over the old one, only where it's revealed, so each frame adds to the
+// previous ones.
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(t, s, in.uv) * in.color;
+
+    var at = revealAt(in.uv);
+    if dim.reverse != 0u {
+        at = 1.0 - at;
+    }
+
+    if at > dim.progress {
+        discard;
+    }
+
+    return color;
+}
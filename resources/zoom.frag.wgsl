@@ -0,0 +1,99 @@
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+}
+
+struct Dim {
+    progress: f32,
+    strength: f32,
+    aspectRatio: f32,
+    // 0: zoom in, 1: zoom out, 2: cross zoom
+    mode: u32,
+}
+
+const SAMPLES: i32 = 24;
+const PI: f32 = 3.14159265;
+
+// the new image
+@group(1) @binding(0)
+var t: texture_2d<f32>;
+
+@group(1) @binding(1)
+var s: sampler;
+
+@group(3) @binding(0)
+var<uniform> dim: Dim;
+
+// the old image
+@group(3) @binding(1)
+var old_image: texture_2d<f32>;
+
+@group(3) @binding(2)
+var old_sampler: sampler;
+
+fn random(p: vec2<f32>) -> f32 {
+    return fract(sin(dot(p, vec2(12.9898, 78.233))) * 43758.5453);
+}
+
+fn inside(p: vec2<f32>) -> f32 {
+    return step(0.0, p.x) * step(p.x, 1.0) * step(0.0, p.y) * step(p.y, 1.0);
+}
+
+// The old image scaled around the center, blurred along the direction of the zoom: the samples
+// are taken between the current scale and the one of a few frames ago.
+// The alpha is the part of the samples that fall inside the image.
+fn zoomed(uv: vec2<f32>, scale: f32, blur: f32) -> vec4<f32> {
+    let center = vec2(0.5, 0.5);
+    let offset = random(uv);
+    var color = vec4(0.0, 0.0, 0.0, 0.0);
+    for (var i = 0; i < SAMPLES; i++) {
+        let percent = (f32(i) + offset) / f32(SAMPLES);
+        let p = center + (uv - center) / (scale * (1.0 - blur * percent));
+        color += vec4(textureSampleLevel(old_image, old_sampler, p, 0.0).rgb, 1.0) * inside(p);
+    }
+    return color / f32(SAMPLES);
+}
+
+// A radial blur toward the center, of both images, while they cross fade.
+fn crossZoom(uv: vec2<f32>) -> vec4<f32> {
+    let center = vec2(0.5, 0.5);
+    let strength = dim.strength * sin(PI * dim.progress);
+    let dissolve = smoothstep(0.3, 0.7, dim.progress);
+    let toCenter = center - uv;
+    let offset = random(uv);
+
+    var color = vec4(0.0, 0.0, 0.0, 0.0);
+    var total = 0.0;
+    for (var i = 0; i < SAMPLES; i++) {
+        let percent = (f32(i) + offset) / f32(SAMPLES);
+        let weight = 4.0 * (percent - percent * percent);
+        let p = uv + toCenter * percent * strength;
+        let fromColor = textureSampleLevel(old_image, old_sampler, p, 0.0);
+        let toColor = textureSampleLevel(t, s, p, 0.0);
+        color += mix(fromColor, toColor, dissolve) * weight;
+        total += weight;
+    }
+    return color / total;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    if dim.mode == 2u {
+        return crossZoom(in.uv) * in.color;
+    }
+
+    let newColor = textureSampleLevel(t, s, in.uv, 0.0) * in.color;
+    let amount = dim.strength * dim.progress;
+
+    var old: vec4<f32>;
+    if dim.mode == 1u {
+        old = zoomed(in.uv, 1.0 / (1.0 + amount), -0.2 * amount);
+    } else {
+        old = zoomed(in.uv, 1.0 + amount, 0.2 * amount);
+    }
+
+    // the old image fades out while it moves, where there's nothing of it the new one is shown
+    let alpha = old.a * (1.0 - dim.progress);
+    return vec4(mix(newColor.rgb, old.rgb / max(old.a, 0.0001), alpha), 1.0);
+}
@@ -0,0 +1,290 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+use ggez::{GameError, GameResult};
+use image::{DynamicImage, GenericImageView};
+
+/// How long an image is shown, when nothing else is given.
+pub const DEFAULT_DURATION: Duration = Duration::from_millis(5_000);
+
+/// The name of the file, in a folder, with the display duration of its images.
+const FOLDER_SIDECAR: &str = ".duration";
+
+/// The extension added to the name of an image, for the file with its display duration, like
+/// `photo.jpg.duration`.
+const IMAGE_SIDECAR: &str = "duration";
+
+/// The extension added to the name of an image, for the file with its caption, like
+/// `photo.jpg.txt`.
+const CAPTION_SIDECAR: &str = "txt";
+
+/// the time added, in auto mode, for every word of the caption
+const TIME_PER_WORD: Duration = Duration::from_millis(250);
+
+/// the aspect ratio, of the longest side over the shortest one, of an image that fills a 16:9
+/// screen, longer images are panoramas
+const SCREEN_RATIO: f32 = 16.0 / 9.0;
+
+/// the maximum factor of the duration of a panorama
+const MAX_PANORAMA_FACTOR: f32 = 3.0;
+
+/// the mean difference of the luminance between adjacent pixels, of a thumbnail, below which an
+/// image has no detail, and above which it has the most detail
+const LOW_DETAIL: f32 = 4.0;
+const HIGH_DETAIL: f32 = 24.0;
+
+/// the factor of the duration of the images with the most detail
+const MAX_DETAIL_FACTOR: f32 = 1.5;
+
+/// The side of the thumbnail used to measure the detail of an image.
+const THUMBNAIL_SIZE: u32 = 128;
+
+/// How long an image is shown: a fixed duration, or a duration that grows for panoramas, for
+/// images with a caption and for images with a lot of detail.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DisplayDuration {
+    Fixed(Duration),
+    /// the base duration, that the automatic rules lengthen
+    Auto(Duration),
+}
+
+/// Parsed from the milliseconds, like `8000`, or `auto`, or `auto:<milliseconds of the base>`.
+impl FromStr for DisplayDuration {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let millis = |value: &str| {
+            value
+                .trim()
+                .parse::<u64>()
+                .map(Duration::from_millis)
+                .map_err(|_| "expected the milliseconds, auto or auto:<milliseconds>".to_string())
+        };
+
+        match s.trim() {
+            "auto" => Ok(DisplayDuration::Auto(DEFAULT_DURATION)),
+            value => match value.strip_prefix("auto:") {
+                Some(base) => Ok(DisplayDuration::Auto(millis(base)?)),
+                None => Ok(DisplayDuration::Fixed(millis(value)?)),
+            },
+        }
+    }
+}
+
+/// The display durations of the images: the one of the sidecar of the image, or the one of the
+/// sidecar of its folder, or the default one.
+pub struct Durations {
+    default: DisplayDuration,
+    /// the durations of the sidecars of the folders, they are read only once
+    folders: HashMap<PathBuf, Option<DisplayDuration>>,
+}
+
+impl Durations {
+    pub fn new(default: DisplayDuration) -> Durations {
+        Durations {
+            default,
+            folders: HashMap::new(),
+        }
+    }
+
+    /// How long the image, read from path, must be shown, an error if one of its sidecars is
+    /// not valid.
+    pub fn duration(&mut self, path: &Path, img: &DynamicImage) -> GameResult<Duration> {
+        Ok(match self.rule(path)? {
+            DisplayDuration::Fixed(duration) => duration,
+            DisplayDuration::Auto(base) => {
+                let (width, height) = img.dimensions();
+                let factor = panorama_factor(width, height) * detail_factor(detail(img));
+
+                base.mul_f32(factor) + caption_time(&read_caption(path))
+            }
+        })
+    }
+
+    fn rule(&mut self, path: &Path) -> GameResult<DisplayDuration> {
+        if let Some(duration) = read_sidecar(&sidecar_path(path, IMAGE_SIDECAR))? {
+            return Ok(duration);
+        }
+
+        let folder = path.parent().unwrap_or_else(|| Path::new(""));
+
+        let folder_duration = match self.folders.get(folder) {
+            Some(duration) => *duration,
+            None => {
+                let duration = read_sidecar(&folder.join(FOLDER_SIDECAR))?;
+                self.folders.insert(folder.to_path_buf(), duration);
+                duration
+            }
+        };
+
+        Ok(folder_duration.unwrap_or(self.default))
+    }
+}
+
+/// The path of the file with the given extension added to the name of the image.
+fn sidecar_path(path: &Path, extension: &str) -> PathBuf {
+    let mut sidecar = path.as_os_str().to_os_string();
+    sidecar.push(".");
+    sidecar.push(extension);
+    PathBuf::from(sidecar)
+}
+
+/// The duration in the sidecar at path, None if there's no sidecar, an error if it's not valid.
+fn read_sidecar(path: &Path) -> GameResult<Option<DisplayDuration>> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(_) => return Ok(None),
+    };
+
+    content.parse().map(Some).map_err(|e| {
+        GameError::CustomError(format!(
+            "Invalid duration '{}' in {}: {}",
+            content.trim(),
+            path.display(),
+            e
+        ))
+    })
+}
+
+/// The caption of the image, empty if it has none.
+fn read_caption(path: &Path) -> String {
+    fs::read_to_string(sidecar_path(path, CAPTION_SIDECAR)).unwrap_or_default()
+}
+
+/// The time to read the caption.
+fn caption_time(caption: &str) -> Duration {
+    TIME_PER_WORD * caption.split_whitespace().count() as u32
+}
+
+/// How much longer a panorama is shown: as many times it's longer than the screen.
+fn panorama_factor(width: u32, height: u32) -> f32 {
+    let ratio = width.max(height) as f32 / width.min(height).max(1) as f32;
+    (ratio / SCREEN_RATIO).clamp(1.0, MAX_PANORAMA_FACTOR)
+}
+
+/// How much longer an image is shown, for its detail.
+fn detail_factor(detail: f32) -> f32 {
+    let amount = ((detail - LOW_DETAIL) / (HIGH_DETAIL - LOW_DETAIL)).clamp(0.0, 1.0);
+    1.0 + amount * (MAX_DETAIL_FACTOR - 1.0)
+}
+
+/// The mean difference of the luminance between adjacent pixels, of a thumbnail of the image.
+fn detail(img: &DynamicImage) -> f32 {
+    let thumbnail = img.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE).to_luma8();
+    let (width, height) = thumbnail.dimensions();
+
+    let mut total = 0.0;
+    let mut count = 0;
+
+    for y in 0..height {
+        for x in 0..width {
+            let value = thumbnail.get_pixel(x, y)[0] as f32;
+
+            if x + 1 < width {
+                total += (value - thumbnail.get_pixel(x + 1, y)[0] as f32).abs();
+                count += 1;
+            }
+
+            if y + 1 < height {
+                total += (value - thumbnail.get_pixel(x, y + 1)[0] as f32).abs();
+                count += 1;
+            }
+        }
+    }
+
+    if count == 0 {
+        0.0
+    } else {
+        total / count as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{ImageBuffer, Luma};
+
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            Ok(DisplayDuration::Fixed(Duration::from_millis(8000))),
+            "8000\n".parse()
+        );
+        assert_eq!(Ok(DisplayDuration::Auto(DEFAULT_DURATION)), "auto".parse());
+        assert_eq!(
+            Ok(DisplayDuration::Auto(Duration::from_millis(3000))),
+            "auto:3000".parse()
+        );
+        assert!("8s".parse::<DisplayDuration>().is_err());
+    }
+
+    #[test]
+    fn test_panorama_factor() {
+        assert_eq!(1.0, panorama_factor(1920, 1080));
+        assert_eq!(1.0, panorama_factor(1080, 1920));
+        assert!((panorama_factor(3840, 1080) - 2.0).abs() < 1e-3);
+        assert_eq!(MAX_PANORAMA_FACTOR, panorama_factor(10000, 100));
+    }
+
+    #[test]
+    fn test_caption_time() {
+        assert_eq!(Duration::ZERO, caption_time(""));
+        assert_eq!(TIME_PER_WORD * 4, caption_time("a day at\nthe"));
+    }
+
+    #[test]
+    fn test_detail() {
+        let flat = DynamicImage::ImageLuma8(ImageBuffer::from_pixel(64, 64, Luma([128])));
+        assert_eq!(1.0, detail_factor(detail(&flat)));
+
+        let checkerboard = DynamicImage::ImageLuma8(ImageBuffer::from_fn(64, 64, |x, y| {
+            Luma([if (x + y) % 2 == 0 { 0 } else { 255 }])
+        }));
+        assert_eq!(MAX_DETAIL_FACTOR, detail_factor(detail(&checkerboard)));
+    }
+
+    #[test]
+    fn test_sidecars() {
+        let folder =
+            std::env::temp_dir().join(format!("slideshow-durations-{}", std::process::id()));
+        fs::create_dir_all(&folder).unwrap();
+        fs::write(folder.join(FOLDER_SIDECAR), "7000").unwrap();
+        fs::write(folder.join("b.jpg.duration"), "9000").unwrap();
+
+        let img = DynamicImage::ImageLuma8(ImageBuffer::from_pixel(16, 9, Luma([0])));
+        let mut durations = Durations::new(DisplayDuration::Fixed(DEFAULT_DURATION));
+
+        assert_eq!(
+            Duration::from_millis(7000),
+            durations.duration(&folder.join("a.jpg"), &img).unwrap()
+        );
+        assert_eq!(
+            Duration::from_millis(9000),
+            durations.duration(&folder.join("b.jpg"), &img).unwrap()
+        );
+        assert_eq!(
+            DEFAULT_DURATION,
+            durations
+                .duration(Path::new("/nonexistent/c.jpg"), &img)
+                .unwrap()
+        );
+
+        // the caption is read in auto mode
+        fs::write(folder.join("d.jpg.duration"), "auto:1000").unwrap();
+        fs::write(folder.join("d.jpg.txt"), "a day at the beach").unwrap();
+        assert_eq!(
+            Duration::from_millis(1000) + TIME_PER_WORD * 5,
+            durations.duration(&folder.join("d.jpg"), &img).unwrap()
+        );
+
+        // an invalid sidecar is an error, like an invalid --duration
+        fs::write(folder.join("e.jpg.duration"), "8s").unwrap();
+        assert!(durations.duration(&folder.join("e.jpg"), &img).is_err());
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+}
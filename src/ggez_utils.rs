@@ -2,7 +2,9 @@ use ggez::graphics::{Canvas, Color, DrawMode, DrawParam, Drawable, Mesh, Rect};
 use ggez::*;
 
 pub type Point2 = nalgebra::Point2<f32>;
+pub type Vector2 = nalgebra::Vector2<f32>;
 
+#[allow(clippy::too_many_arguments)]
 pub fn draw_rect(
     ctx: &mut Context,
     canvas: &mut Canvas,
@@ -23,6 +25,51 @@ pub fn draw_rect(
     Ok(())
 }
 
+/// Sutherland–Hodgman clipping of a convex polygon, keeping the half plane where
+/// `point · normal <= limit`.
+pub fn clip(polygon: &[Point2], normal: Vector2, limit: f32) -> Vec<Point2> {
+    let mut result = Vec::new();
+
+    let distance = |p: &Point2| p.coords.dot(&normal) - limit;
+
+    for (i, current) in polygon.iter().enumerate() {
+        let next = &polygon[(i + 1) % polygon.len()];
+        let current_distance = distance(current);
+        let next_distance = distance(next);
+
+        if current_distance <= 0.0 {
+            result.push(*current);
+        }
+
+        if (current_distance < 0.0 && next_distance > 0.0)
+            || (current_distance > 0.0 && next_distance < 0.0)
+        {
+            let t = current_distance / (current_distance - next_distance);
+            result.push(current + (next - current) * t);
+        }
+    }
+
+    result
+}
+
+/// The area of a polygon, with the shoelace formula, for the tests of the transitions that draw
+/// polygons.
+#[cfg(test)]
+pub fn polygon_area(polygon: &[Point2]) -> f32 {
+    let mut area = 0.0;
+    for (i, p) in polygon.iter().enumerate() {
+        let next = &polygon[(i + 1) % polygon.len()];
+        area += p.x * next.y - next.x * p.y;
+    }
+    area.abs() / 2.0
+}
+
+/// The area covered by rectangles that do not overlap.
+#[cfg(test)]
+pub fn rects_area(rects: &[Rect]) -> f32 {
+    rects.iter().map(|it| it.w * it.h).sum()
+}
+
 /*
 pub fn draw_fps(ctx: &mut Context, world: &World, font: graphics::Font, color: Color) -> GameResult<()> {
     let fps = fps(ctx).round() as i32;
@@ -13,7 +13,9 @@ use ggez::*;
 
 use crate::slideshow::*;
 
+mod durations;
 mod ggez_utils;
+mod random;
 mod slideshow;
 mod sync_timer;
 mod transitions;
@@ -37,7 +39,9 @@ fn build_context_and_run(args: Vec<String>) -> Result<(), GameError> {
 
     let window_mode = conf::WindowMode::default()
         .dimensions(1920.0, 1080.0)
-        .fullscreen_type(FullscreenType::Windowed);
+        .fullscreen_type(FullscreenType::Windowed)
+        .resizable(true)
+        .resize_on_scale_factor_change(true);
 
     let cb = ContextBuilder::new("slideshow", "enricobn")
         .window_setup(conf::WindowSetup::default().title("Slideshow").vsync(true))
@@ -46,10 +50,9 @@ fn build_context_and_run(args: Vec<String>) -> Result<(), GameError> {
 
     let (mut ctx, events_loop) = cb.build()?;
 
-    let screen =
-        graphics::ScreenImage::new(&mut ctx, graphics::ImageFormat::Rgba8UnormSrgb, 1., 1., 1);
+    let screen = graphics::ScreenImage::new(&ctx, graphics::ImageFormat::Rgba8UnormSrgb, 1., 1., 1);
 
-    let state = SlideShow::new(args, screen);
+    let state = SlideShow::new(&mut ctx, args, screen)?;
 
     event::run(ctx, events_loop, state)
 }
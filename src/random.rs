@@ -0,0 +1,37 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// The random generator of the slideshow, shared by the shuffle of the images and by the
+/// transitions, so a run with the same seed is the same.
+pub type SharedRng = Rc<RefCell<StdRng>>;
+
+/// A generator from the seed.
+pub fn shared_rng(seed: u64) -> SharedRng {
+    Rc::new(RefCell::new(StdRng::seed_from_u64(seed)))
+}
+
+/// A seed for a run without one, it's printed, so the run can be repeated.
+pub fn random_seed() -> u64 {
+    rand::thread_rng().gen()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_seed_same_sequence() {
+        let sequence = |seed| {
+            let rng = shared_rng(seed);
+            let mut values: Vec<u32> = (0..20).collect();
+            rng.borrow_mut().shuffle(&mut values);
+            values
+        };
+
+        assert_eq!(sequence(42), sequence(42));
+        assert_ne!(sequence(42), sequence(43));
+    }
+}
@@ -1,32 +1,95 @@
 use std::fmt::Display;
 use std::ops::Sub;
 use std::path::Path;
+use std::str::FromStr;
 use std::thread;
 use std::time::{Duration, SystemTime};
 
 use chrono::{DateTime, Utc};
 use ggez::event::EventHandler;
-use ggez::graphics::{Image, ImageFormat, Rect, ScreenImage};
+use ggez::graphics::{Canvas, Color, DrawParam, Drawable, Image, ImageFormat, ScreenImage};
+use ggez::input::keyboard::{KeyCode, KeyInput};
 use ggez::*;
-use image;
 use image::imageops::CatmullRom;
-use image::{GenericImage, GenericImageView, ImageBuffer};
+use image::{DynamicImage, GenericImage, GenericImageView, ImageBuffer};
 use rand::Rng;
 
+use crate::durations::{DisplayDuration, Durations, DEFAULT_DURATION};
+use crate::random::{random_seed, shared_rng};
 use crate::sync_timer::*;
+use crate::transitions::combinators::{Blend, Reverse, Sequence, TimeScale};
+use crate::transitions::dip::Dip;
 use crate::transitions::distortion::Distortion;
 use crate::transitions::fade::Fade;
+use crate::transitions::gl_transition::GlTransition;
+use crate::transitions::kaleidoscope::Kaleidoscope;
+use crate::transitions::luma::Luma;
+use crate::transitions::luma_fade::LumaFade;
+use crate::transitions::page_curl::PageCurl;
+use crate::transitions::params::TransitionParams;
+use crate::transitions::particles::Particles;
 use crate::transitions::pixels::Pixels;
 use crate::transitions::quads::Quads;
+use crate::transitions::ripple::Ripple;
+use crate::transitions::rotation::Rotation;
+use crate::transitions::shaders::Shaders;
+use crate::transitions::shatter::Shatter;
 use crate::transitions::slides::Slides;
 use crate::transitions::sphere::Sphere;
-use crate::transitions::transition::{SimpleTransition, Transition};
+use crate::transitions::swirl::Swirl;
+use crate::transitions::transition::{frames_to_draw, SimpleTransition, Transition};
+use crate::transitions::wgsl_transition::WgslTransition;
+use crate::transitions::wipes::Wipe;
+use crate::transitions::zoom::{Zoom, ZoomMode};
+
+/// how much the interrupted frame fades every frame, when the transition is cross-faded
+const CROSS_FADE_STEP: f32 = 0.05;
+
+/// the range of the speed of the slideshow, the keys double it or halve it
+const MIN_SPEED: f32 = 0.25;
+const MAX_SPEED: f32 = 4.0;
+
+/// What happens to the running transition when the next image is requested before it ends.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Interrupt {
+    /// the transition ends at once, and the next one starts from its image
+    Snap,
+    /// the frame shown when the transition is interrupted fades out over the next image
+    CrossFade,
+}
+
+impl FromStr for Interrupt {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "snap" => Ok(Interrupt::Snap),
+            "crossfade" => Ok(Interrupt::CrossFade),
+            _ => Err("expected one of snap, crossfade".to_string()),
+        }
+    }
+}
+
+/// The events of the timer of the slideshow.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SlideShowEvent {
+    /// the current image has been shown for its duration
+    NextImage,
+}
 
-const LOAD_IMAGE_DELAY: u64 = 5_000; // millis
-const UPDATE_DELAY: u64 = 1000 / 60; // millis
+/// The frame shown when the transition has been interrupted, fading out over the next image.
+struct Interruption {
+    frame: Image,
+    alpha: f32,
+}
 
 pub struct SlideShow {
-    timer: SyncTimer,
+    /// The time of the slideshow, shared by the timer and the transitions, it can be paused and
+    /// its speed changed.
+    clock: PlaybackClock,
+    timer: SyncTimer<SlideShowEvent>,
+    /// the fractions of the frames of the transition not drawn yet, at the speed of the clock
+    pending_frames: f32,
     file_names: Vec<String>,
     file_index: usize,
     transition: Box<dyn Transition>,
@@ -35,10 +98,35 @@ pub struct SlideShow {
     image_updated: bool,
     last_time: SystemTime,
     screen_image_buffer: ScreenImage,
+    /// The last loaded image, as read from disk, so it can be fitted again when the window is resized.
+    current_image: Option<DynamicImage>,
+    /// The last loaded image, fitted to the drawable area.
+    fitted_image: Option<Image>,
+    interrupt: Interrupt,
+    interruption: Option<Interruption>,
+    next_requested: bool,
+    durations: Durations,
+    /// The shaders of the transitions, built once and shared, or built again when they change.
+    shaders: Shaders,
+    /// How long the current image is shown, after its transition.
+    display_duration: Duration,
 }
 
 impl SlideShow {
-    pub fn new(args: Vec<String>, screen_image_buffer: ScreenImage) -> SlideShow {
+    pub fn new(
+        ctx: &mut Context,
+        args: Vec<String>,
+        screen_image_buffer: ScreenImage,
+    ) -> GameResult<SlideShow> {
+        let (options, args): (Vec<String>, Vec<String>) =
+            args.into_iter().partition(|it| it.starts_with("--"));
+
+        let interrupt = option(&options, "interrupt")?.unwrap_or(Interrupt::CrossFade);
+        let seed = option(&options, "seed")?.unwrap_or_else(random_seed);
+        let duration =
+            option(&options, "duration")?.unwrap_or(DisplayDuration::Fixed(DEFAULT_DURATION));
+        let speed = speed(&options)?;
+
         let folder_name = args.get(1);
 
         if folder_name.is_none() {
@@ -46,22 +134,22 @@ impl SlideShow {
             panic!();
         }
 
-        let transition: Box<dyn Transition> = match args.get(2) {
-            Some(s) => match s.as_str() {
-                "simple" => Box::new(SimpleTransition::new()),
-                "pixels" => Box::new(Pixels::new()),
-                "quads" => Box::new(Quads::new()),
-                "slide" => Box::new(Slides::new(1)),
-                "slides" => Box::new(Slides::new(8)),
-                "fade" => Box::new(Fade::new()),
-                "distortion" => Box::new(Distortion::new()),
-                "sphere" => Box::new(Sphere::new()),
-                _ => {
-                    panic!("Unknown transition {}", s);
-                }
-            },
-            None => Box::new(Fade::new()),
-        };
+        let params = TransitionParams::parse(args.get(2).map_or("fade", |it| it.as_str()))?;
+        let mut transition = create_transition(ctx, &params)?;
+        params.check_unused()?;
+
+        // the shaders are built now, so the first transition does not hitch
+        let mut shaders = Shaders::new();
+        transition.prepare(ctx, &mut shaders)?;
+
+        let clock = PlaybackClock::new();
+        clock.set_scale(speed);
+        transition.set_clock(clock.clone());
+
+        // the seed is printed, so the run can be repeated
+        println!("seed {}", seed);
+        let rng = shared_rng(seed);
+        transition.set_rng(rng.clone());
 
         let directory = Path::new(folder_name.unwrap());
 
@@ -69,18 +157,16 @@ impl SlideShow {
 
         let mut file_names = Vec::new();
 
-        for path in paths {
-            if let Ok(entry) = path {
-                if let Ok(file_type) = entry.file_type() {
-                    if file_type.is_file() {
-                        let file_name = String::from(entry.path().to_str().unwrap());
-                        if file_name.to_uppercase().ends_with("PNG")
-                            || file_name.to_uppercase().ends_with("JPG")
-                            || file_name.to_uppercase().ends_with("JPEG")
-                            || file_name.to_uppercase().ends_with("BMP")
-                        {
-                            file_names.push(file_name);
-                        }
+        for entry in paths.flatten() {
+            if let Ok(file_type) = entry.file_type() {
+                if file_type.is_file() {
+                    let file_name = String::from(entry.path().to_str().unwrap());
+                    if file_name.to_uppercase().ends_with("PNG")
+                        || file_name.to_uppercase().ends_with("JPG")
+                        || file_name.to_uppercase().ends_with("JPEG")
+                        || file_name.to_uppercase().ends_with("BMP")
+                    {
+                        file_names.push(file_name);
                     }
                 }
             }
@@ -91,12 +177,12 @@ impl SlideShow {
             panic!();
         }
 
-        let mut rng = rand::thread_rng();
-
-        rng.shuffle(&mut file_names);
+        // the order of the files in the folder is not always the same
+        file_names.sort();
+        rng.borrow_mut().shuffle(&mut file_names);
 
-        let timer = SyncTimer::new();
-        //timer.add(SyncEvent::new("next_image", Duration::from_millis(0), false));
+        let timer = SyncTimer::with_clock(clock.clone());
+        //timer.add(SyncEvent::new(SlideShowEvent::NextImage, Duration::from_millis(0), false));
         /*timer.add(SyncEvent::new(
             "draw",
             Duration::from_millis(UPDATE_DELAY),
@@ -105,8 +191,10 @@ impl SlideShow {
 
          */
 
-        SlideShow {
+        Ok(SlideShow {
+            clock,
             timer,
+            pending_frames: 0.0,
             file_names,
             file_index: 0,
             transition,
@@ -115,7 +203,15 @@ impl SlideShow {
             image_updated: false,
             last_time: SystemTime::now(),
             screen_image_buffer,
-        }
+            current_image: None,
+            fitted_image: None,
+            interrupt,
+            interruption: None,
+            next_requested: false,
+            durations: Durations::new(duration),
+            shaders,
+            display_duration: DEFAULT_DURATION,
+        })
     }
 
     fn update_image(&mut self, ctx: &mut Context) -> GameResult<()> {
@@ -128,56 +224,120 @@ impl SlideShow {
             self.file_index = 0;
         }
 
-        let img = image::open(&file_name).unwrap();
+        let img = image::open(file_name).unwrap();
 
-        let rect = Rect {
-            x: 0.,
-            y: 0.,
-            w: ctx.gfx.drawable_size().0,
-            h: ctx.gfx.drawable_size().1,
-        };
+        self.display_duration = self.durations.duration(Path::new(file_name), &img)?;
 
-        let width = rect.w;
-        let height = rect.h;
+        let image = fit_image(ctx, &img)?;
+        self.current_image = Some(img);
+        self.fitted_image = Some(image.clone());
 
-        let scale_x = width / img.width() as f32;
-        let scale_y = height / img.height() as f32;
+        self.transition.update_image(ctx, image);
+        self.waiting_for_next_image = false;
 
-        let scale = if scale_x < scale_y { scale_x } else { scale_y };
+        Ok(())
+    }
 
-        let black = image::Rgba { 0: [0, 0, 0, 255] };
+    /// Shows the next image now, interrupting the running transition, if any.
+    fn next_image(&mut self, ctx: &mut Context) -> GameResult<()> {
+        if self.waiting_for_next_image {
+            self.timer.remove(SlideShowEvent::NextImage);
+            self.update_image(ctx)?;
+            self.image_updated = true;
+            return Ok(());
+        }
 
-        let img = img.resize(
-            (img.width() as f32 * scale) as u32,
-            (img.height() as f32 * scale) as u32,
-            CatmullRom,
-        );
+        match self.interrupt {
+            Interrupt::Snap => {
+                self.transition.skip_to_end(ctx)?;
+                self.interruption = None;
 
-        let mut img_rgba = ImageBuffer::from_pixel(width as u32, height as u32, black);
-
-        img_rgba
-            .copy_from(
-                &img,
-                ((width - img.width() as f32) / 2.0) as u32,
-                ((height - img.height() as f32) / 2.0) as u32,
-            )
-            .map_err(|it| GameError::CustomError(it.to_string()))?;
-
-        let image = Image::from_pixels(
-            ctx,
-            img_rgba.as_raw(),
-            ImageFormat::Rgba8UnormSrgb,
-            img_rgba.width(),
-            img_rgba.height(),
-        );
+                // the next transition starts from the end of this one
+                let mut canvas =
+                    Canvas::from_screen_image(ctx, &mut self.screen_image_buffer, Color::BLACK);
+                if let Some(image) = &self.fitted_image {
+                    image.draw(&mut canvas, DrawParam::default());
+                }
+                canvas.finish(ctx)?;
 
-        self.transition.update_image(ctx, image);
-        self.waiting_for_next_image = false;
+                self.update_image(ctx)?;
+            }
+            Interrupt::CrossFade => {
+                // a copy, since the screen is drawn over
+                let screen = self.screen_image_buffer.image(ctx);
+                let frame = Image::new_canvas_image(
+                    ctx,
+                    screen.format(),
+                    screen.width(),
+                    screen.height(),
+                    1,
+                );
+                let mut canvas = Canvas::from_image(ctx, frame.clone(), Color::BLACK);
+                screen.draw(&mut canvas, DrawParam::default());
+                canvas.finish(ctx)?;
+
+                self.transition.skip_to_end(ctx)?;
+                self.update_image(ctx)?;
+                // the next image is reached by the cross fade, not by the transition
+                self.transition.skip_to_end(ctx)?;
+
+                self.interruption = Some(Interruption { frame, alpha: 1.0 });
+            }
+        }
 
+        self.image_updated = true;
         Ok(())
     }
 
-    fn wait(&mut self, ctx: &mut Context) {
+    /// Draws the next frame of the cross fade of the interrupted transition, returns true while
+    /// it's running.
+    fn draw_interruption(&mut self, canvas: &mut Canvas) -> bool {
+        let interruption = match &mut self.interruption {
+            Some(interruption) => interruption,
+            None => return false,
+        };
+
+        interruption.alpha -= CROSS_FADE_STEP;
+
+        if let Some(image) = &self.fitted_image {
+            image.draw(canvas, DrawParam::default());
+
+            let frame = &interruption.frame;
+            let param = DrawParam::default()
+                .scale([
+                    image.width() as f32 / frame.width() as f32,
+                    image.height() as f32 / frame.height() as f32,
+                ])
+                .color(Color::new(1.0, 1.0, 1.0, interruption.alpha.max(0.0)));
+            frame.draw(canvas, param);
+        }
+
+        if interruption.alpha <= 0.0 {
+            self.interruption = None;
+            return false;
+        }
+
+        true
+    }
+
+    fn toggle_pause(&mut self) {
+        if self.clock.is_paused() {
+            self.clock.resume();
+            println!("resumed");
+        } else {
+            self.clock.pause();
+            println!("paused");
+        }
+    }
+
+    /// Multiplies the speed by factor, within the range of the speed.
+    fn change_speed(&mut self, factor: f32) {
+        let speed = (self.clock.scale() * factor).clamp(MIN_SPEED, MAX_SPEED);
+        self.clock.set_scale(speed);
+        println!("speed {}x", speed);
+    }
+
+    fn wait(&mut self) {
         let frame_time = Duration::from_millis(1_000 / 30);
         let duration = self.last_time.elapsed().unwrap();
         if duration.lt(&frame_time) {
@@ -189,6 +349,7 @@ impl SlideShow {
         self.last_time = SystemTime::now();
     }
 
+    #[allow(dead_code)]
     fn get_time_format(&self) -> impl Display {
         let system_time = SystemTime::now();
         let datetime: DateTime<Utc> = system_time.into();
@@ -199,48 +360,269 @@ impl SlideShow {
 
 impl EventHandler<GameError> for SlideShow {
     fn update(&mut self, ctx: &mut Context) -> GameResult<()> {
-        if self.first || self.timer.fired().iter().any(|it| it == &"next_image") {
+        if self.first || self.timer.fired().contains(&SlideShowEvent::NextImage) {
             self.update_image(ctx)?;
             self.image_updated = true;
             self.first = false;
         }
 
+        if self.next_requested {
+            self.next_requested = false;
+            self.next_image(ctx)?;
+        }
+
+        if self.transition.poll(ctx, &mut self.shaders) && self.waiting_for_next_image {
+            // the transition runs again, the image is shown for its duration when it ends
+            self.timer.remove(SlideShowEvent::NextImage);
+            self.waiting_for_next_image = false;
+        }
+
         if self.waiting_for_next_image {
-            self.wait(ctx);
+            self.wait();
             return Ok(());
         }
         Ok(())
     }
 
+    fn key_down_event(
+        &mut self,
+        ctx: &mut Context,
+        input: KeyInput,
+        _repeated: bool,
+    ) -> GameResult {
+        match input.keycode {
+            Some(KeyCode::Escape) => ctx.request_quit(),
+            Some(KeyCode::Right) | Some(KeyCode::Space) => self.next_requested = true,
+            Some(KeyCode::P) => self.toggle_pause(),
+            Some(KeyCode::Plus) | Some(KeyCode::Equals) | Some(KeyCode::NumpadAdd) => {
+                self.change_speed(2.0)
+            }
+            Some(KeyCode::Minus) | Some(KeyCode::NumpadSubtract) => self.change_speed(0.5),
+            _ => {}
+        }
+        Ok(())
+    }
+
     fn draw(&mut self, ctx: &mut Context) -> GameResult<()> {
         if self.image_updated {
             ctx.gfx.present(&self.screen_image_buffer.image(ctx))?;
             self.image_updated = false;
-            self.wait(ctx);
+            self.wait();
             return Ok(());
         }
 
         if self.waiting_for_next_image {
             ctx.gfx.present(&self.screen_image_buffer.image(ctx))?;
-            self.wait(ctx);
+            self.wait();
             return Ok(());
         }
 
         let mut canvas =
             graphics::Canvas::from_screen_image(ctx, &mut self.screen_image_buffer, None);
 
-        let transaction_finished = !self.transition.draw(ctx, &mut canvas)?;
+        // the transition follows the clock: while it's paused no frame is drawn, so the canvas
+        // keeps the last one, and at a higher speed more frames are drawn at once
+        let frames = if self.clock.is_paused() {
+            0
+        } else {
+            frames_to_draw(&mut self.pending_frames, self.clock.scale())
+        };
+
+        let mut transaction_finished = false;
+
+        for _ in 0..frames {
+            transaction_finished = if self.interruption.is_some() {
+                !self.draw_interruption(&mut canvas)
+            } else {
+                !self.transition.draw(ctx, &mut canvas)?
+            };
+
+            if transaction_finished {
+                break;
+            }
+        }
         canvas.finish(ctx)?;
         ctx.gfx.present(&self.screen_image_buffer.image(ctx))?;
 
         if transaction_finished && !self.waiting_for_next_image {
             self.timer.add(SyncEvent::new(
-                "next_image",
-                Duration::from_millis(LOAD_IMAGE_DELAY),
+                SlideShowEvent::NextImage,
+                self.display_duration,
                 false,
             ));
             self.waiting_for_next_image = true;
         }
         Ok(())
     }
+
+    fn resize_event(&mut self, ctx: &mut Context, width: f32, height: f32) -> GameResult<()> {
+        self.screen_image_buffer = ScreenImage::new(ctx, ImageFormat::Rgba8UnormSrgb, 1., 1., 1);
+
+        let mut canvas =
+            Canvas::from_screen_image(ctx, &mut self.screen_image_buffer, Color::BLACK);
+
+        // a minimized window has no drawable area, the image is fitted again when it's restored
+        if width < 1.0 || height < 1.0 {
+            canvas.finish(ctx)?;
+            return Ok(());
+        }
+
+        if let Some(ref img) = self.current_image {
+            let image = fit_image(ctx, img)?;
+
+            if self.waiting_for_next_image {
+                image.draw(&mut canvas, DrawParam::default());
+            } else {
+                self.transition.resize(ctx, image.clone());
+
+                if self.interruption.is_some() {
+                    // the image is reached by the cross fade, not by the transition
+                    self.transition.skip_to_end(ctx)?;
+                }
+            }
+
+            self.fitted_image = Some(image);
+        }
+
+        canvas.finish(ctx)?;
+        self.image_updated = true;
+
+        Ok(())
+    }
+}
+
+fn create_transition(
+    ctx: &mut Context,
+    params: &TransitionParams,
+) -> GameResult<Box<dyn Transition>> {
+    let transition: Box<dyn Transition> = match params.name() {
+        "simple" => Box::new(SimpleTransition::new()),
+        "pixels" => Box::new(Pixels::from_params(params)?),
+        "quads" => Box::new(Quads::from_params(params)?),
+        "slides" | "slide" => Box::new(Slides::from_params(params)?),
+        "fade" => Box::new(Fade::new()),
+        "dip" => Box::new(Dip::from_params(params)?),
+        "lumafade" => Box::new(LumaFade::from_params(params)?),
+        "luma" => Box::new(Luma::from_params(params)?),
+        "distortion" => Box::new(Distortion::from_params(params)?),
+        "sphere" => Box::new(Sphere::from_params(params)?),
+        "glsl" => Box::new(GlTransition::from_params(ctx, params)?),
+        "wgsl" => Box::new(WgslTransition::from_params(params)?),
+        "particles" => Box::new(Particles::from_params(params)?),
+        "shatter" => Box::new(Shatter::from_params(params)?),
+        "pagecurl" => Box::new(PageCurl::from_params(params)?),
+        "swirl" => Box::new(Swirl::from_params(params)?),
+        "ripple" => Box::new(Ripple::from_params(params)?),
+        "kaleidoscope" => Box::new(Kaleidoscope::from_params(params)?),
+        "blinds" | "checkerboard" | "iris" | "clockwipe" => Box::new(Wipe::from_params(params)?),
+        "cube" | "flip" => Box::new(Rotation::from_params(params)?),
+        "zoom" => Box::new(Zoom::from_params(params, ZoomMode::In)?),
+        "crosszoom" => Box::new(Zoom::from_params(params, ZoomMode::Cross)?),
+        "sequence" => Box::new(Sequence::from_params(ctx, params, create_transition)?),
+        "blend" => Box::new(Blend::from_params(ctx, params, create_transition)?),
+        "reverse" => Box::new(Reverse::from_params(ctx, params, create_transition)?),
+        "timescale" => Box::new(TimeScale::from_params(ctx, params, create_transition)?),
+        name => {
+            return Err(GameError::CustomError(format!(
+                "Unknown transition {}",
+                name
+            )));
+        }
+    };
+
+    Ok(transition)
+}
+
+/// The value of the option `--name=value` in the command line options, None if it's not given.
+fn option<T>(options: &[String], name: &str) -> GameResult<Option<T>>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    let prefix = format!("--{}=", name);
+
+    match options.iter().find_map(|it| it.strip_prefix(&prefix)) {
+        Some(value) => value.parse().map(Some).map_err(|e| {
+            GameError::CustomError(format!("Invalid value '{}' for --{}: {}", value, name, e))
+        }),
+        None => Ok(None),
+    }
+}
+
+/// The speed in the command line options, 1 if it's not given.
+fn speed(options: &[String]) -> GameResult<f32> {
+    let speed = option(options, "speed")?.unwrap_or(1.0);
+
+    if !(MIN_SPEED..=MAX_SPEED).contains(&speed) {
+        return Err(GameError::CustomError(format!(
+            "Invalid value '{}' for --speed: expected a value from {} to {}",
+            speed, MIN_SPEED, MAX_SPEED
+        )));
+    }
+
+    Ok(speed)
+}
+
+/// Scales the image to fit the drawable area, keeping its aspect ratio, and centers it on a black
+/// background of the same size of the drawable area.
+fn fit_image(ctx: &mut Context, img: &DynamicImage) -> GameResult<Image> {
+    let (width, height) = ctx.gfx.drawable_size();
+
+    let scale_x = width / img.width() as f32;
+    let scale_y = height / img.height() as f32;
+
+    let scale = if scale_x < scale_y { scale_x } else { scale_y };
+
+    let black = image::Rgba([0, 0, 0, 255]);
+
+    let img = img.resize(
+        (img.width() as f32 * scale) as u32,
+        (img.height() as f32 * scale) as u32,
+        CatmullRom,
+    );
+
+    let mut img_rgba = ImageBuffer::from_pixel(width as u32, height as u32, black);
+
+    img_rgba
+        .copy_from(
+            &img,
+            ((width - img.width() as f32) / 2.0) as u32,
+            ((height - img.height() as f32) / 2.0) as u32,
+        )
+        .map_err(|it| GameError::CustomError(it.to_string()))?;
+
+    Ok(Image::from_pixels(
+        ctx,
+        img_rgba.as_raw(),
+        ImageFormat::Rgba8UnormSrgb,
+        img_rgba.width(),
+        img_rgba.height(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_option() {
+        let options = vec!["--interrupt=snap".to_string()];
+
+        assert_eq!(
+            Some(Interrupt::Snap),
+            option::<Interrupt>(&options, "interrupt").unwrap()
+        );
+        assert_eq!(None, option::<u64>(&options, "seed").unwrap());
+
+        let options = vec!["--interrupt=fast".to_string()];
+        assert!(option::<Interrupt>(&options, "interrupt").is_err());
+    }
+
+    #[test]
+    fn test_speed() {
+        assert_eq!(1.0, speed(&[]).unwrap());
+        assert_eq!(0.25, speed(&["--speed=0.25".to_string()]).unwrap());
+        assert!(speed(&["--speed=8".to_string()]).is_err());
+        assert!(speed(&["--speed=fast".to_string()]).is_err());
+    }
 }
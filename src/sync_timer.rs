@@ -1,66 +1,429 @@
-use std::ops::Sub;
+#[cfg(test)]
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::rc::Rc;
 use std::time::Duration;
 use std::time::Instant;
 
-pub struct SyncTimer {
+/// Where the timer reads the time from.
+pub trait Clock {
+    /// The time elapsed since the start of the clock.
+    fn now(&self) -> Duration;
+}
+
+/// The clock of the system.
+pub struct RealClock {
     start: Instant,
-    events: Vec<SyncEvent>,
 }
 
-impl SyncTimer {
-    pub fn new() -> SyncTimer {
-        SyncTimer {
+impl RealClock {
+    pub fn new() -> RealClock {
+        RealClock {
             start: Instant::now(),
+        }
+    }
+}
+
+impl Clock for RealClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+impl<C: Clock + ?Sized> Clock for Box<C> {
+    fn now(&self) -> Duration {
+        (**self).now()
+    }
+}
+
+/// A clock that moves only when it's advanced, the clones share the same time, so a clone can
+/// be given to the timer and the other one advanced.
+#[cfg(test)]
+#[derive(Clone, Default)]
+pub struct ManualClock {
+    now: Rc<Cell<Duration>>,
+}
+
+#[cfg(test)]
+impl ManualClock {
+    pub fn new() -> ManualClock {
+        ManualClock::default()
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        self.now.set(self.now.get() + duration);
+    }
+}
+
+#[cfg(test)]
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        self.now.get()
+    }
+}
+
+/// The time of the slideshow: it follows its source, but it stops while it's paused, and it runs
+/// faster or slower by the scale. The clones share the same time, so the timer and the
+/// transitions can read the same one.
+pub struct PlaybackClock<C = Box<dyn Clock>> {
+    state: Rc<RefCell<Playback<C>>>,
+}
+
+// not derived, since the source does not need to be cloned
+impl<C> Clone for PlaybackClock<C> {
+    fn clone(&self) -> Self {
+        PlaybackClock {
+            state: Rc::clone(&self.state),
+        }
+    }
+}
+
+struct Playback<C> {
+    source: C,
+    /// the time of the playback
+    time: Duration,
+    /// the time of the source when the time of the playback has been updated
+    last_source: Duration,
+    paused: bool,
+    scale: f32,
+}
+
+impl PlaybackClock {
+    pub fn new() -> PlaybackClock {
+        PlaybackClock::with_source(Box::new(RealClock::new()))
+    }
+}
+
+impl<C: Clock + 'static> PlaybackClock<C> {
+    /// A clock that follows this one, so it stops while this one is paused, and runs faster or
+    /// slower by its own scale over the one of this clock; the time starts now.
+    pub fn scaled(&self, scale: f32) -> PlaybackClock {
+        let clock = PlaybackClock::with_source(Box::new(self.clone()) as Box<dyn Clock>);
+        clock.set_scale(scale);
+        clock
+    }
+}
+
+impl<C: Clock> PlaybackClock<C> {
+    pub fn with_source(source: C) -> PlaybackClock<C> {
+        let last_source = source.now();
+
+        PlaybackClock {
+            state: Rc::new(RefCell::new(Playback {
+                source,
+                time: Duration::ZERO,
+                last_source,
+                paused: false,
+                scale: 1.0,
+            })),
+        }
+    }
+
+    /// Stops the time, the events of the timers do not fire until it's resumed.
+    pub fn pause(&self) {
+        let mut state = self.state.borrow_mut();
+        state.update();
+        state.paused = true;
+    }
+
+    pub fn resume(&self) {
+        let mut state = self.state.borrow_mut();
+        state.update();
+        state.paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.state.borrow().paused
+    }
+
+    /// How fast the time runs, 2 is twice as fast as the source, so the events fire in half the
+    /// time.
+    pub fn set_scale(&self, scale: f32) {
+        let mut state = self.state.borrow_mut();
+        state.update();
+        state.scale = scale.max(0.0);
+    }
+
+    pub fn scale(&self) -> f32 {
+        self.state.borrow().scale
+    }
+}
+
+impl<C: Clock> Playback<C> {
+    /// Updates the time from the source, and returns it.
+    fn update(&mut self) -> Duration {
+        let source = self.source.now();
+        let elapsed = source.saturating_sub(self.last_source);
+        self.last_source = source;
+
+        if !self.paused {
+            // in nanoseconds, so at scale 1 it's exactly the time of the source
+            let scaled = elapsed.as_nanos() as f64 * self.scale as f64;
+            self.time += Duration::from_nanos(scaled.round() as u64);
+        }
+
+        self.time
+    }
+}
+
+impl<C: Clock> Clock for PlaybackClock<C> {
+    fn now(&self) -> Duration {
+        self.state.borrow_mut().update()
+    }
+}
+
+/// Fires events after a time, once or recurring, the time is the one of the clock, by default the
+/// playback clock of the slideshow.
+pub struct SyncTimer<E, C = PlaybackClock> {
+    clock: C,
+    events: Vec<SyncEvent<E>>,
+}
+
+impl<E: Copy + PartialEq, C: Clock> SyncTimer<E, C> {
+    pub fn with_clock(clock: C) -> SyncTimer<E, C> {
+        SyncTimer {
+            clock,
             events: Vec::new(),
         }
     }
 
-    pub fn add(&mut self, event: SyncEvent) {
+    /// Adds the event, its time starts now.
+    pub fn add(&mut self, mut event: SyncEvent<E>) {
+        event.start = self.clock.now();
         self.events.push(event);
     }
 
-    pub fn fired(&mut self) -> Vec<&'static str> {
+    /// Removes the events with the given id, so they never fire.
+    pub fn remove(&mut self, id: E) {
+        self.events.retain(|event| event.id != id);
+    }
+
+    /// The ids of the events whose time has elapsed, in the order they have been added; the
+    /// one-shot events are removed, the recurring ones start again, even if more than one period
+    /// has elapsed, they fire once.
+    pub fn fired(&mut self) -> Vec<E> {
+        let now = self.clock.now();
         let mut result = Vec::new();
-        let mut to_remove = Vec::new();
-
-        let now = Instant::now();
-        let mut index: usize = 0;
-        for event in self.events.iter_mut() {
-            let elapsed = now.sub(event.start);
-
-            if elapsed >= event.after {
-                if event.recurring {
-                    event.start = now;
-                } else {
-                    to_remove.push(index);
-                }
-                result.push(event.id);
+
+        self.events.retain_mut(|event| {
+            if now - event.start < event.after {
+                return true;
             }
-            index = index + 1;
-        }
 
-        for i in to_remove.iter() {
-            self.events.remove(*i);
-        }
+            result.push(event.id);
+
+            if event.recurring {
+                event.start = now;
+            }
+
+            event.recurring
+        });
 
         result
     }
 }
 
-pub struct SyncEvent {
-    id: &'static str,
-    start: Instant,
+pub struct SyncEvent<E> {
+    id: E,
+    /// the time of the clock when the event has been added, or when it has fired, if recurring
+    start: Duration,
     after: Duration,
     recurring: bool,
 }
 
-impl SyncEvent {
-    pub fn new(id: &'static str, after: Duration, recurring: bool) -> SyncEvent {
+impl<E> SyncEvent<E> {
+    pub fn new(id: E, after: Duration, recurring: bool) -> SyncEvent<E> {
         SyncEvent {
             id,
-            start: Instant::now(),
+            start: Duration::ZERO,
             after,
             recurring,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum Event {
+        A,
+        B,
+        C,
+    }
+
+    fn millis(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    fn timer() -> (SyncTimer<Event, ManualClock>, ManualClock) {
+        let clock = ManualClock::new();
+        (SyncTimer::with_clock(clock.clone()), clock)
+    }
+
+    #[test]
+    fn test_one_shot() {
+        let (mut timer, clock) = timer();
+        timer.add(SyncEvent::new(Event::A, millis(100), false));
+
+        clock.advance(millis(99));
+        assert!(timer.fired().is_empty());
+
+        clock.advance(millis(1));
+        assert_eq!(vec![Event::A], timer.fired());
+
+        clock.advance(millis(1000));
+        assert!(timer.fired().is_empty());
+    }
+
+    #[test]
+    fn test_recurring() {
+        let (mut timer, clock) = timer();
+        timer.add(SyncEvent::new(Event::A, millis(100), true));
+
+        let mut count = 0;
+        for _ in 0..10 {
+            clock.advance(millis(50));
+            count += timer.fired().len();
+        }
+
+        assert_eq!(5, count);
+
+        // more periods elapsed, it fires once
+        clock.advance(millis(1000));
+        assert_eq!(vec![Event::A], timer.fired());
+    }
+
+    #[test]
+    fn test_simultaneous() {
+        let (mut timer, clock) = timer();
+        timer.add(SyncEvent::new(Event::A, millis(100), false));
+        timer.add(SyncEvent::new(Event::B, millis(500), false));
+        timer.add(SyncEvent::new(Event::C, millis(100), false));
+
+        clock.advance(millis(100));
+        assert_eq!(vec![Event::A, Event::C], timer.fired());
+
+        // only B is left
+        clock.advance(millis(400));
+        assert_eq!(vec![Event::B], timer.fired());
+        assert!(timer.events.is_empty());
+    }
+
+    #[test]
+    fn test_simultaneous_with_recurring() {
+        let (mut timer, clock) = timer();
+        timer.add(SyncEvent::new(Event::A, millis(100), false));
+        timer.add(SyncEvent::new(Event::B, millis(100), true));
+        timer.add(SyncEvent::new(Event::C, millis(100), false));
+
+        clock.advance(millis(100));
+        assert_eq!(vec![Event::A, Event::B, Event::C], timer.fired());
+
+        clock.advance(millis(100));
+        assert_eq!(vec![Event::B], timer.fired());
+    }
+
+    #[test]
+    fn test_remove() {
+        let (mut timer, clock) = timer();
+        timer.add(SyncEvent::new(Event::A, millis(100), false));
+        timer.add(SyncEvent::new(Event::B, millis(100), false));
+        timer.remove(Event::A);
+
+        clock.advance(millis(100));
+        assert_eq!(vec![Event::B], timer.fired());
+    }
+
+    fn playback_timer() -> (
+        SyncTimer<Event, PlaybackClock<ManualClock>>,
+        PlaybackClock<ManualClock>,
+        ManualClock,
+    ) {
+        let source = ManualClock::new();
+        let playback = PlaybackClock::with_source(source.clone());
+        (SyncTimer::with_clock(playback.clone()), playback, source)
+    }
+
+    #[test]
+    fn test_pause_and_resume() {
+        let (mut timer, playback, clock) = playback_timer();
+        timer.add(SyncEvent::new(Event::A, millis(100), false));
+
+        clock.advance(millis(60));
+        playback.pause();
+        assert!(playback.is_paused());
+
+        clock.advance(millis(1000));
+        assert!(timer.fired().is_empty());
+
+        playback.resume();
+        clock.advance(millis(39));
+        assert!(timer.fired().is_empty());
+
+        clock.advance(millis(1));
+        assert_eq!(vec![Event::A], timer.fired());
+    }
+
+    #[test]
+    fn test_scale() {
+        let (mut timer, playback, clock) = playback_timer();
+        timer.add(SyncEvent::new(Event::A, millis(100), false));
+
+        clock.advance(millis(20));
+        playback.set_scale(2.0);
+
+        // 20 at scale 1, then 40 at scale 2
+        clock.advance(millis(39));
+        assert!(timer.fired().is_empty());
+
+        clock.advance(millis(1));
+        assert_eq!(vec![Event::A], timer.fired());
+    }
+
+    #[test]
+    fn test_clones_share_the_time() {
+        let clock = ManualClock::new();
+        let playback = PlaybackClock::with_source(clock.clone());
+        let other = playback.clone();
+
+        clock.advance(millis(10));
+        playback.set_scale(0.5);
+        clock.advance(millis(10));
+
+        assert_eq!(millis(15), other.now());
+        assert_eq!(0.5, other.scale());
+    }
+
+    #[test]
+    fn test_scaled_follows_the_parent() {
+        let clock = ManualClock::new();
+        let playback = PlaybackClock::with_source(clock.clone());
+        playback.set_scale(2.0);
+        let scaled = playback.scaled(0.5);
+
+        clock.advance(millis(10));
+        assert_eq!(millis(10), scaled.now());
+
+        playback.pause();
+        clock.advance(millis(10));
+        assert_eq!(millis(10), scaled.now());
+
+        playback.resume();
+        scaled.set_scale(3.0);
+        clock.advance(millis(10));
+        assert_eq!(millis(70), scaled.now());
+    }
+
+    #[test]
+    fn test_added_later_starts_then() {
+        let (mut timer, clock) = timer();
+
+        clock.advance(millis(1000));
+        timer.add(SyncEvent::new(Event::A, millis(100), false));
+
+        clock.advance(millis(50));
+        assert!(timer.fired().is_empty());
+    }
+}
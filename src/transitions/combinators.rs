@@ -0,0 +1,759 @@
+use ggez::graphics::{Canvas, Color, DrawParam, Drawable, Image, ImageFormat};
+use ggez::*;
+
+use crate::random::SharedRng;
+use crate::sync_timer::PlaybackClock;
+use crate::transitions::dip::DipColor;
+use crate::transitions::params::{split_nested, unwrap_parens, TransitionParams};
+use crate::transitions::shaders::Shaders;
+use crate::transitions::transition::{frames_to_draw, ImagePair, Transition};
+use crate::velocity::*;
+
+/// how much the progress of Reverse advances every frame, at velocity 1
+const STEP: f32 = 0.01;
+
+/// Creates a transition from its parameters, it's how the combinators create their children.
+pub type TransitionFactory = fn(&mut Context, &TransitionParams) -> GameResult<Box<dyn Transition>>;
+
+/// The spec of a child transition, in the parameters of a combinator, is the spec of a transition,
+/// between parentheses if it has more than one parameter, like
+/// `blend:first=(distortion:amplitude=0.1,frequency=3),second=fade`; the child can be a combinator
+/// with its own children between parentheses.
+fn child_params(spec: &str) -> GameResult<TransitionParams> {
+    TransitionParams::parse(unwrap_parens(spec))
+}
+
+fn create_child(
+    ctx: &mut Context,
+    spec: &str,
+    factory: TransitionFactory,
+) -> GameResult<Box<dyn Transition>> {
+    let params = child_params(spec)?;
+    let child = factory(ctx, &params)?;
+    params.check_unused()?;
+    Ok(child)
+}
+
+/// The child transition with the spec in the given parameter, that is mandatory.
+fn child(
+    ctx: &mut Context,
+    params: &TransitionParams,
+    key: &str,
+    factory: TransitionFactory,
+) -> GameResult<Box<dyn Transition>> {
+    match params.get_opt::<String>(key)? {
+        Some(spec) => create_child(ctx, &spec, factory),
+        None => Err(GameError::CustomError(format!(
+            "Missing parameter {} of transition {}",
+            key,
+            params.name()
+        ))),
+    }
+}
+
+/// An image that can be drawn on, of the given size, with source stretched over it, or black.
+fn canvas_image(
+    ctx: &mut Context,
+    width: u32,
+    height: u32,
+    source: Option<&Image>,
+) -> GameResult<Image> {
+    let image = Image::new_canvas_image(ctx, ImageFormat::Rgba8UnormSrgb, width, height, 1);
+    let mut canvas = Canvas::from_image(ctx, image.clone(), Color::BLACK);
+
+    if let Some(source) = source {
+        source.draw(&mut canvas, stretch(source, width, height));
+    }
+
+    canvas.finish(ctx)?;
+    Ok(image)
+}
+
+/// The parameter to draw the image stretched to the given size.
+fn stretch(image: &Image, width: u32, height: u32) -> DrawParam {
+    DrawParam::new().scale([
+        width as f32 / image.width() as f32,
+        height as f32 / image.height() as f32,
+    ])
+}
+
+/// The images where a child of a Sequence starts and ends.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Stage {
+    Old,
+    Color,
+    New,
+}
+
+/// The image where the child at index, of count children, starts: the first starts from the old
+/// image, the others alternate between the color and the new image, so that the last one goes from
+/// the color to the new image, and no child starts and ends on the same image.
+fn stage(index: usize, count: usize) -> Stage {
+    if index == 0 {
+        Stage::Old
+    } else if (count - index) % 2 == 1 {
+        Stage::Color
+    } else {
+        Stage::New
+    }
+}
+
+/// The stages where the child at index, of count children, starts and ends.
+fn stages(index: usize, count: usize) -> (Stage, Stage) {
+    (stage(index, count), stage(index + 1, count))
+}
+
+/// Runs the children one after the other, each one starts from the image where the previous one
+/// has ended, through the color between them, and the last one ends on the new image.
+pub struct Sequence {
+    children: Vec<Box<dyn Transition>>,
+    color: Color,
+    image: Option<Image>,
+    last_image: Option<Image>,
+    /// the running child, None when the sequence has ended
+    current: Option<usize>,
+    /// true if the running child has already got its images
+    started: bool,
+}
+
+impl Sequence {
+    pub fn new(children: Vec<Box<dyn Transition>>, color: Color) -> Sequence {
+        Sequence {
+            children,
+            color,
+            image: None,
+            last_image: None,
+            current: None,
+            started: false,
+        }
+    }
+
+    /// Parameters:
+    /// - transitions: the specs of the children, separated by '+', like `sphere+(swirl:strength=3)`
+    /// - color: the color between the children, black, white, or the hex value (default black)
+    pub fn from_params(
+        ctx: &mut Context,
+        params: &TransitionParams,
+        factory: TransitionFactory,
+    ) -> GameResult<Sequence> {
+        let specs = params.get("transitions", String::new())?;
+
+        let children = split_nested(&specs, '+')?
+            .into_iter()
+            .filter(|it| !it.trim().is_empty())
+            .map(|spec| create_child(ctx, spec, factory))
+            .collect::<GameResult<Vec<_>>>()?;
+
+        if children.is_empty() {
+            return Err(GameError::CustomError(
+                "Missing parameter transitions of transition sequence".to_string(),
+            ));
+        }
+
+        Ok(Sequence::new(
+            children,
+            params.get("color", DipColor(Color::BLACK))?.0,
+        ))
+    }
+
+    /// The image of the stage, with the size of the new image; the old image is black before
+    /// the first one.
+    fn stage_image(&self, ctx: &mut Context, stage: Stage, image: &Image) -> Image {
+        match (stage, &self.last_image) {
+            (Stage::Old, Some(last_image)) => last_image.clone(),
+            (Stage::Old, None) => {
+                Image::from_color(ctx, image.width(), image.height(), Some(Color::BLACK))
+            }
+            (Stage::Color, _) => {
+                Image::from_color(ctx, image.width(), image.height(), Some(self.color))
+            }
+            (Stage::New, _) => image.clone(),
+        }
+    }
+
+    /// Gives to the child its images: the one where the previous child has ended, and the one
+    /// where it must end.
+    fn start(&mut self, ctx: &mut Context, index: usize) {
+        let image = match &self.image {
+            Some(image) => image.clone(),
+            None => return,
+        };
+
+        let (from, to) = stages(index, self.children.len());
+        let from = self.stage_image(ctx, from, &image);
+        let to = self.stage_image(ctx, to, &image);
+
+        self.children[index].set_images(ctx, from, to);
+    }
+}
+
+impl Transition for Sequence {
+    fn prepare(&mut self, ctx: &mut Context, shaders: &mut Shaders) -> GameResult {
+        for child in self.children.iter_mut() {
+            child.prepare(ctx, shaders)?;
+        }
+        Ok(())
+    }
+
+    fn set_clock(&mut self, clock: PlaybackClock) {
+        for child in self.children.iter_mut() {
+            child.set_clock(clock.clone());
+        }
+    }
+
+    /// The children build again their shaders, but they do not run again alone.
+    fn poll(&mut self, ctx: &mut Context, shaders: &mut Shaders) -> bool {
+        for child in self.children.iter_mut() {
+            child.poll(ctx, shaders);
+        }
+        false
+    }
+
+    fn set_rng(&mut self, rng: SharedRng) {
+        for child in self.children.iter_mut() {
+            child.set_rng(rng.clone());
+        }
+    }
+
+    fn draw(&mut self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult<bool> {
+        let index = match self.current {
+            Some(index) => index,
+            None => return Ok(false),
+        };
+
+        if !self.started {
+            self.start(ctx, index);
+            self.started = true;
+        }
+
+        if self.children[index].draw(ctx, canvas)? {
+            return Ok(true);
+        }
+
+        if index + 1 < self.children.len() {
+            self.current = Some(index + 1);
+            self.started = false;
+            Ok(true)
+        } else {
+            self.current = None;
+            Ok(false)
+        }
+    }
+
+    fn skip_to_end(&mut self, _ctx: &mut Context) -> GameResult {
+        // the children get their images when they start, so they can be left where they are
+        self.current = None;
+        Ok(())
+    }
+
+    fn update_image(&mut self, _ctx: &mut Context, image: Image) {
+        self.last_image = self.image.replace(image);
+        self.current = Some(0);
+        self.started = false;
+    }
+
+    fn resize(&mut self, ctx: &mut Context, image: Image) {
+        if let (Some(index), true) = (self.current, self.started) {
+            // the running child goes on, toward the color or the new image, at the new size
+            let (_, to) = stages(index, self.children.len());
+            let to = self.stage_image(ctx, to, &image);
+            self.children[index].resize(ctx, to);
+        }
+
+        self.image = Some(image);
+    }
+}
+
+/// Runs two children at the same time, each on its own image, and draws the second over the
+/// first, with the given weight.
+pub struct Blend {
+    children: [Box<dyn Transition>; 2],
+    weight: f32,
+    image: Option<Image>,
+    last_image: Option<Image>,
+    /// where the children draw
+    buffers: Option<[Image; 2]>,
+    running: [bool; 2],
+}
+
+impl Blend {
+    /// weight is the opacity of the second child, from 0 to 1.
+    pub fn new(first: Box<dyn Transition>, second: Box<dyn Transition>, weight: f32) -> Blend {
+        Blend {
+            children: [first, second],
+            weight: weight.clamp(0.0, 1.0),
+            image: None,
+            last_image: None,
+            buffers: None,
+            running: [false, false],
+        }
+    }
+
+    /// Parameters:
+    /// - first, second: the specs of the children, like `distortion:amplitude=0.1;frequency=3`
+    /// - weight: the opacity of the second child over the first (default 0.5)
+    pub fn from_params(
+        ctx: &mut Context,
+        params: &TransitionParams,
+        factory: TransitionFactory,
+    ) -> GameResult<Blend> {
+        Ok(Blend::new(
+            child(ctx, params, "first", factory)?,
+            child(ctx, params, "second", factory)?,
+            params.get("weight", 0.5)?,
+        ))
+    }
+
+    /// The buffers start with the old image, since some children draw only what changes; when
+    /// the size changes they are stretched to the new one.
+    fn update_buffers(&mut self, ctx: &mut Context, width: u32, height: u32) -> GameResult {
+        let sources = match self.buffers.take() {
+            Some([first, second]) if first.width() == width && first.height() == height => {
+                self.buffers = Some([first, second]);
+                return Ok(());
+            }
+            Some([first, second]) => [Some(first), Some(second)],
+            None => [self.last_image.clone(), self.last_image.clone()],
+        };
+
+        self.buffers = Some([
+            canvas_image(ctx, width, height, sources[0].as_ref())?,
+            canvas_image(ctx, width, height, sources[1].as_ref())?,
+        ]);
+
+        Ok(())
+    }
+}
+
+impl Transition for Blend {
+    fn prepare(&mut self, ctx: &mut Context, shaders: &mut Shaders) -> GameResult {
+        for child in self.children.iter_mut() {
+            child.prepare(ctx, shaders)?;
+        }
+        Ok(())
+    }
+
+    fn set_clock(&mut self, clock: PlaybackClock) {
+        for child in self.children.iter_mut() {
+            child.set_clock(clock.clone());
+        }
+    }
+
+    /// The children build again their shaders, but they do not run again alone.
+    fn poll(&mut self, ctx: &mut Context, shaders: &mut Shaders) -> bool {
+        for child in self.children.iter_mut() {
+            child.poll(ctx, shaders);
+        }
+        false
+    }
+
+    fn set_rng(&mut self, rng: SharedRng) {
+        for child in self.children.iter_mut() {
+            child.set_rng(rng.clone());
+        }
+    }
+
+    fn draw(&mut self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult<bool> {
+        if !self.running.contains(&true) {
+            return Ok(false);
+        }
+
+        let (width, height) = match &self.image {
+            Some(image) => (image.width(), image.height()),
+            None => return Ok(false),
+        };
+
+        self.update_buffers(ctx, width, height)?;
+
+        if let Some(buffers) = &self.buffers {
+            for ((child, buffer), running) in self
+                .children
+                .iter_mut()
+                .zip(buffers.iter())
+                .zip(self.running.iter_mut())
+            {
+                if *running {
+                    let mut child_canvas = Canvas::from_image(ctx, buffer.clone(), None);
+                    *running = child.draw(ctx, &mut child_canvas)?;
+                    child_canvas.finish(ctx)?;
+                }
+            }
+
+            buffers[0].draw(canvas, DrawParam::new());
+            buffers[1].draw(
+                canvas,
+                DrawParam::new().color(Color::new(1.0, 1.0, 1.0, self.weight)),
+            );
+        }
+
+        Ok(self.running.contains(&true))
+    }
+
+    fn skip_to_end(&mut self, ctx: &mut Context) -> GameResult {
+        for child in self.children.iter_mut() {
+            child.skip_to_end(ctx)?;
+        }
+
+        self.running = [false, false];
+        Ok(())
+    }
+
+    fn update_image(&mut self, ctx: &mut Context, image: Image) {
+        for child in self.children.iter_mut() {
+            child.update_image(ctx, image.clone());
+        }
+
+        self.last_image = self.image.replace(image);
+        self.buffers = None;
+        self.running = [true, true];
+    }
+
+    fn set_images(&mut self, ctx: &mut Context, from: Image, to: Image) {
+        for child in self.children.iter_mut() {
+            child.set_images(ctx, from.clone(), to.clone());
+        }
+
+        self.last_image = Some(from);
+        self.image = Some(to);
+        self.buffers = None;
+        self.running = [true, true];
+    }
+
+    fn resize(&mut self, ctx: &mut Context, image: Image) {
+        // the buffers are stretched to the new size when they are drawn
+        for child in self.children.iter_mut() {
+            child.resize(ctx, image.clone());
+        }
+
+        self.image = Some(image);
+    }
+}
+
+/// Runs the child backward: the child goes from the new image to the old one, and it's drawn at
+/// the progress going from 1 to 0, so it's seen going from the old image to the new one; only
+/// the reversible children, that can be drawn at any progress, can run backward.
+pub struct Reverse {
+    child: Box<dyn Transition>,
+    images: ImagePair,
+    progress: Progress,
+    velocity: Box<dyn Velocity>,
+    ended: bool,
+}
+
+impl Reverse {
+    pub fn new(child: Box<dyn Transition>) -> GameResult<Reverse> {
+        if !child.reversible() {
+            return Err(GameError::CustomError(
+                "the transition of reverse cannot run backward, since it cannot be drawn at \
+                 any progress"
+                    .to_string(),
+            ));
+        }
+
+        Ok(Reverse {
+            child,
+            images: ImagePair::default(),
+            progress: Progress::default(),
+            velocity: Box::new(Easing::Linear),
+            ended: true,
+        })
+    }
+
+    /// Parameters:
+    /// - transition: the spec of the child, like `swirl:strength=3`
+    /// - easing: the easing curve of the progress of the child, see Easing (default linear)
+    pub fn from_params(
+        ctx: &mut Context,
+        params: &TransitionParams,
+        factory: TransitionFactory,
+    ) -> GameResult<Reverse> {
+        let mut reverse = Reverse::new(child(ctx, params, "transition", factory)?)?;
+
+        if let Some(easing) = params.get_opt::<Easing>("easing")? {
+            reverse.velocity = Box::new(easing);
+        }
+
+        Ok(reverse)
+    }
+
+    /// Gives to the child the images, so it goes from the new image to the old one.
+    fn update_child(&mut self, ctx: &mut Context) {
+        if let (Some(image), Some(last_image)) = (self.images.image(), self.images.last_image()) {
+            self.child
+                .set_images(ctx, image.clone(), last_image.clone());
+        }
+    }
+}
+
+impl Transition for Reverse {
+    fn prepare(&mut self, ctx: &mut Context, shaders: &mut Shaders) -> GameResult {
+        self.child.prepare(ctx, shaders)
+    }
+
+    fn set_clock(&mut self, clock: PlaybackClock) {
+        self.child.set_clock(clock);
+    }
+
+    /// The child builds again its shaders, the next frames are drawn with them.
+    fn poll(&mut self, ctx: &mut Context, shaders: &mut Shaders) -> bool {
+        self.child.poll(ctx, shaders);
+        false
+    }
+
+    fn set_rng(&mut self, rng: SharedRng) {
+        self.child.set_rng(rng);
+    }
+
+    fn draw(&mut self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult<bool> {
+        if self.ended {
+            return Ok(false);
+        }
+
+        self.progress.advance(self.velocity.as_ref(), STEP);
+        self.child.draw_at(ctx, canvas, 1.0 - self.progress.value)?;
+
+        if self.progress.ended() {
+            self.ended = true;
+        }
+
+        Ok(!self.ended)
+    }
+
+    fn reversible(&self) -> bool {
+        true
+    }
+
+    fn draw_at(&mut self, ctx: &mut Context, canvas: &mut Canvas, progress: f32) -> GameResult {
+        self.child.draw_at(ctx, canvas, 1.0 - progress)
+    }
+
+    fn skip_to_end(&mut self, _ctx: &mut Context) -> GameResult {
+        self.ended = true;
+        Ok(())
+    }
+
+    fn update_image(&mut self, ctx: &mut Context, image: Image) {
+        let from = self.images.next_from(ctx, &image);
+        self.set_images(ctx, from, image);
+    }
+
+    fn set_images(&mut self, ctx: &mut Context, from: Image, to: Image) {
+        self.images.set(from, to);
+        self.update_child(ctx);
+        self.progress = Progress::default();
+        self.ended = false;
+    }
+
+    fn resize(&mut self, ctx: &mut Context, image: Image) {
+        // the child gets again both images, the progress is the one of the reverse, so it goes
+        // on from where it is
+        self.images.resize(image);
+        self.update_child(ctx);
+    }
+}
+
+/// Runs the child faster or slower: it's drawn speed times per frame, on average, and its clock
+/// runs speed times as fast.
+pub struct TimeScale {
+    child: Box<dyn Transition>,
+    speed: f32,
+    /// the frames of the child not drawn yet, they are drawn when they are at least one
+    pending: f32,
+    ended: bool,
+}
+
+impl TimeScale {
+    pub fn new(child: Box<dyn Transition>, speed: f32) -> TimeScale {
+        TimeScale {
+            child,
+            speed: speed.max(0.01),
+            pending: 0.0,
+            ended: true,
+        }
+    }
+
+    /// Parameters:
+    /// - transition: the spec of the child, like `swirl:strength=3`
+    /// - speed: how much faster the child runs, less than 1 to run it slower (default 2)
+    pub fn from_params(
+        ctx: &mut Context,
+        params: &TransitionParams,
+        factory: TransitionFactory,
+    ) -> GameResult<TimeScale> {
+        Ok(TimeScale::new(
+            child(ctx, params, "transition", factory)?,
+            params.get("speed", 2.0)?,
+        ))
+    }
+}
+
+impl Transition for TimeScale {
+    fn prepare(&mut self, ctx: &mut Context, shaders: &mut Shaders) -> GameResult {
+        self.child.prepare(ctx, shaders)
+    }
+
+    fn set_clock(&mut self, clock: PlaybackClock) {
+        // the time of the child runs at its speed too, like its frames
+        self.child.set_clock(clock.scaled(self.speed));
+    }
+
+    fn poll(&mut self, ctx: &mut Context, shaders: &mut Shaders) -> bool {
+        if !self.child.poll(ctx, shaders) {
+            return false;
+        }
+
+        self.pending = 0.0;
+        self.ended = false;
+        true
+    }
+
+    fn set_rng(&mut self, rng: SharedRng) {
+        self.child.set_rng(rng);
+    }
+
+    fn draw(&mut self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult<bool> {
+        if self.ended {
+            return Ok(false);
+        }
+
+        // when no frame is drawn, the canvas keeps the last one
+        for _ in 0..frames_to_draw(&mut self.pending, self.speed) {
+            if !self.child.draw(ctx, canvas)? {
+                self.ended = true;
+                break;
+            }
+        }
+
+        Ok(!self.ended)
+    }
+
+    fn reversible(&self) -> bool {
+        self.child.reversible()
+    }
+
+    fn draw_at(&mut self, ctx: &mut Context, canvas: &mut Canvas, progress: f32) -> GameResult {
+        self.child.draw_at(ctx, canvas, progress)
+    }
+
+    fn skip_to_end(&mut self, ctx: &mut Context) -> GameResult {
+        self.child.skip_to_end(ctx)?;
+        self.ended = true;
+        Ok(())
+    }
+
+    fn update_image(&mut self, ctx: &mut Context, image: Image) {
+        self.child.update_image(ctx, image);
+        self.pending = 0.0;
+        self.ended = false;
+    }
+
+    fn set_images(&mut self, ctx: &mut Context, from: Image, to: Image) {
+        self.child.set_images(ctx, from, to);
+        self.pending = 0.0;
+        self.ended = false;
+    }
+
+    fn resize(&mut self, ctx: &mut Context, image: Image) {
+        self.child.resize(ctx, image);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::transitions::quads::{Quads, QuadsGrid, QuadsOrder};
+    use crate::transitions::swirl::Swirl;
+
+    use super::*;
+
+    #[test]
+    fn test_sequence_stages() {
+        assert_eq!((Stage::Old, Stage::New), stages(0, 1));
+
+        assert_eq!((Stage::Old, Stage::Color), stages(0, 2));
+        // the second child goes from the color, where the first has ended, to the new image
+        assert_eq!((Stage::Color, Stage::New), stages(1, 2));
+
+        assert_eq!((Stage::Old, Stage::New), stages(0, 3));
+        assert_eq!((Stage::New, Stage::Color), stages(1, 3));
+        assert_eq!((Stage::Color, Stage::New), stages(2, 3));
+
+        for count in 1..6 {
+            for index in 0..count {
+                let (from, to) = stages(index, count);
+                assert_ne!(from, to);
+            }
+            assert_eq!(Stage::New, stages(count - 1, count).1);
+        }
+    }
+
+    #[test]
+    fn test_child_params() {
+        let params = TransitionParams::parse(
+            "blend:first=(distortion:amplitude=0.1,frequency=3),second=fade,weight=0.3",
+        )
+        .unwrap();
+
+        let first = child_params(&params.get_opt::<String>("first").unwrap().unwrap()).unwrap();
+        assert_eq!("distortion", first.name());
+        assert_eq!(0.1, first.get("amplitude", 0.0).unwrap());
+        assert_eq!(3, first.get("frequency", 0).unwrap());
+        first.check_unused().unwrap();
+
+        let second = child_params(&params.get_opt::<String>("second").unwrap().unwrap()).unwrap();
+        assert_eq!("fade", second.name());
+        assert_eq!(0.3, params.get("weight", 0.5).unwrap());
+    }
+
+    #[test]
+    fn test_nested_child_params() {
+        let params = TransitionParams::parse(
+            "reverse:transition=(timescale:transition=(swirl:strength=3,radius=0.5),speed=2),\
+             easing=linear",
+        )
+        .unwrap();
+
+        let child =
+            child_params(&params.get_opt::<String>("transition").unwrap().unwrap()).unwrap();
+        assert_eq!("timescale", child.name());
+        assert_eq!(2.0, child.get("speed", 1.0).unwrap());
+
+        let grandchild =
+            child_params(&child.get_opt::<String>("transition").unwrap().unwrap()).unwrap();
+        assert_eq!("swirl", grandchild.name());
+        assert_eq!(3.0, grandchild.get("strength", 0.0).unwrap());
+        assert_eq!(0.5, grandchild.get("radius", 0.0).unwrap());
+        grandchild.check_unused().unwrap();
+    }
+
+    #[test]
+    fn test_nested_sequence_params() {
+        let params = TransitionParams::parse(
+            "sequence:transitions=(swirl:strength=3,radius=0.5)+(sequence:transitions=(fade+dip))",
+        )
+        .unwrap();
+
+        let specs = params.get_opt::<String>("transitions").unwrap().unwrap();
+        let specs = split_nested(&specs, '+').unwrap();
+        assert_eq!(2, specs.len());
+
+        let first = child_params(specs[0]).unwrap();
+        assert_eq!("swirl", first.name());
+        assert_eq!(0.5, first.get("radius", 0.0).unwrap());
+
+        let second = child_params(specs[1]).unwrap();
+        assert_eq!("sequence", second.name());
+        let grandchildren = second.get_opt::<String>("transitions").unwrap().unwrap();
+        assert_eq!(
+            vec!["fade", "dip"],
+            split_nested(&grandchildren, '+').unwrap()
+        );
+    }
+
+    #[test]
+    fn test_reverse_only_reversible_children() {
+        assert!(Reverse::new(Box::new(Swirl::new(5.0, 0.5, 0.5, 0.5))).is_ok());
+        assert!(
+            Reverse::new(Box::new(Quads::new(QuadsGrid::Rows(8), QuadsOrder::Random))).is_err()
+        );
+    }
+}
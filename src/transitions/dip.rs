@@ -0,0 +1,246 @@
+use std::str::FromStr;
+use std::time::Duration;
+
+use ggez::graphics::{Canvas, Color, DrawMode, DrawParam, Drawable, Image};
+use ggez::*;
+
+use crate::ggez_utils::{draw_rect, Point2};
+use crate::sync_timer::{Clock, PlaybackClock};
+use crate::transitions::params::TransitionParams;
+use crate::transitions::transition::Transition;
+
+/// how much the fade advances every frame, for the fade out and the fade in
+const STEP: f32 = 0.02;
+
+/// A color parameter: black, white, or the hex value, like ff8000 or #ff8000.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DipColor(pub Color);
+
+impl FromStr for DipColor {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "black" => return Ok(DipColor(Color::BLACK)),
+            "white" => return Ok(DipColor(Color::WHITE)),
+            _ => {}
+        }
+
+        let hex = s.strip_prefix('#').unwrap_or(s);
+
+        if hex.len() != 6 {
+            return Err("expected black, white or a hex color like ff8000".to_string());
+        }
+
+        let value = u32::from_str_radix(hex, 16).map_err(|e| e.to_string())?;
+
+        Ok(DipColor(Color::from_rgb(
+            (value >> 16) as u8,
+            (value >> 8) as u8,
+            value as u8,
+        )))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DipPhase {
+    /// the old image fades to the color
+    Out(f32),
+    /// only the color, since the time of the clock
+    Hold(Duration),
+    /// the new image fades in from the color
+    In(f32),
+    Ended,
+}
+
+/// A fade through a color: the old image fades to the color, that holds for a while, then the new
+/// image fades in.
+pub struct Dip {
+    color: Color,
+    hold: Duration,
+    phase: DipPhase,
+    clock: PlaybackClock,
+    image: Option<Image>,
+    last_image: Option<Image>,
+}
+
+impl Dip {
+    pub fn new(color: Color, hold: Duration) -> Dip {
+        Dip {
+            color,
+            hold,
+            phase: DipPhase::Ended,
+            clock: PlaybackClock::new(),
+            image: None,
+            last_image: None,
+        }
+    }
+
+    /// Parameters:
+    /// - color: black (default), white, or the hex value, like ff8000
+    /// - hold: how long the color is held in the middle, in milliseconds (default 500)
+    pub fn from_params(params: &TransitionParams) -> GameResult<Dip> {
+        Ok(Dip::new(
+            params.get("color", DipColor(Color::BLACK))?.0,
+            Duration::from_millis(params.get("hold", 500)?),
+        ))
+    }
+
+    fn next_phase(&self, phase: DipPhase, now: Duration) -> DipPhase {
+        match phase {
+            DipPhase::Out(alpha) if alpha + STEP >= 1.0 => DipPhase::Hold(now),
+            DipPhase::Out(alpha) => DipPhase::Out(alpha + STEP),
+            DipPhase::Hold(start) if now.saturating_sub(start) >= self.hold => DipPhase::In(0.0),
+            DipPhase::Hold(start) => DipPhase::Hold(start),
+            DipPhase::In(alpha) if alpha + STEP >= 1.0 => DipPhase::Ended,
+            DipPhase::In(alpha) => DipPhase::In(alpha + STEP),
+            DipPhase::Ended => DipPhase::Ended,
+        }
+    }
+
+    fn draw_color(&self, ctx: &mut Context, canvas: &mut Canvas, image: &Image) -> GameResult {
+        draw_rect(
+            ctx,
+            canvas,
+            0.0,
+            0.0,
+            image.width() as f32,
+            image.height() as f32,
+            &self.color,
+            DrawMode::fill(),
+        )
+    }
+}
+
+impl Transition for Dip {
+    fn set_clock(&mut self, clock: PlaybackClock) {
+        self.clock = clock;
+    }
+
+    fn draw(&mut self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult<bool> {
+        if self.phase == DipPhase::Ended {
+            return Ok(false);
+        }
+
+        self.phase = self.next_phase(self.phase, self.clock.now());
+
+        if let Some(image) = &self.image {
+            match self.phase {
+                DipPhase::Out(alpha) => {
+                    if let Some(last_image) = &self.last_image {
+                        let param = DrawParam::new().dest(Point2::new(0.0, 0.0)).scale([
+                            image.width() as f32 / last_image.width() as f32,
+                            image.height() as f32 / last_image.height() as f32,
+                        ]);
+                        last_image.draw(canvas, param);
+                    }
+
+                    let mut color = self.color;
+                    color.a = alpha;
+                    draw_rect(
+                        ctx,
+                        canvas,
+                        0.0,
+                        0.0,
+                        image.width() as f32,
+                        image.height() as f32,
+                        &color,
+                        DrawMode::fill(),
+                    )?;
+                }
+                DipPhase::Hold(_) => self.draw_color(ctx, canvas, image)?,
+                DipPhase::In(alpha) => {
+                    self.draw_color(ctx, canvas, image)?;
+                    let param = DrawParam::new()
+                        .dest(Point2::new(0.0, 0.0))
+                        .color(Color::new(1.0, 1.0, 1.0, alpha));
+                    image.draw(canvas, param);
+                }
+                DipPhase::Ended => image.draw(canvas, DrawParam::new().dest(Point2::new(0.0, 0.0))),
+            }
+        }
+
+        Ok(self.phase != DipPhase::Ended)
+    }
+
+    fn skip_to_end(&mut self, _ctx: &mut Context) -> GameResult {
+        self.phase = DipPhase::Ended;
+        Ok(())
+    }
+
+    fn update_image(&mut self, _ctx: &mut Context, image: Image) {
+        self.last_image = self.image.replace(image);
+
+        // the first image fades in from the color
+        self.phase = match self.last_image {
+            Some(_) => DipPhase::Out(0.0),
+            None => DipPhase::In(0.0),
+        };
+    }
+
+    fn set_images(&mut self, _ctx: &mut Context, from: Image, to: Image) {
+        self.last_image = Some(from);
+        self.image = Some(to);
+        self.phase = DipPhase::Out(0.0);
+    }
+
+    fn resize(&mut self, _ctx: &mut Context, image: Image) {
+        // the old image is scaled to the size of the new one when it's drawn
+        self.image = Some(image);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(Ok(DipColor(Color::WHITE)), "white".parse());
+        assert_eq!(
+            Ok(DipColor(Color::from_rgb(255, 128, 0))),
+            "#ff8000".parse()
+        );
+        assert_eq!(Ok(DipColor(Color::from_rgb(0, 0, 16))), "000010".parse());
+        assert!("red".parse::<DipColor>().is_err());
+        assert!("gg0000".parse::<DipColor>().is_err());
+    }
+
+    #[test]
+    fn test_phases() {
+        let dip = Dip::new(Color::BLACK, Duration::from_millis(0));
+        let now = Duration::ZERO;
+
+        let mut phase = DipPhase::Out(0.0);
+        let mut phases = Vec::new();
+
+        while phase != DipPhase::Ended {
+            phase = dip.next_phase(phase, now);
+            phases.push(match phase {
+                DipPhase::Out(_) => 'o',
+                DipPhase::Hold(_) => 'h',
+                DipPhase::In(_) => 'i',
+                DipPhase::Ended => 'e',
+            });
+        }
+
+        let mut order = phases.clone();
+        order.dedup();
+
+        // fade out, hold, fade in
+        assert_eq!(vec!['o', 'h', 'i', 'e'], order);
+        assert!(phases.len() > 90 && phases.len() < 110, "{}", phases.len());
+    }
+
+    #[test]
+    fn test_hold() {
+        let dip = Dip::new(Color::BLACK, Duration::from_millis(500));
+        let start = Duration::from_millis(1000);
+
+        let phase = dip.next_phase(DipPhase::Hold(start), start + Duration::from_millis(100));
+        assert_eq!(DipPhase::Hold(start), phase);
+
+        let phase = dip.next_phase(DipPhase::Hold(start), start + Duration::from_millis(500));
+        assert_eq!(DipPhase::In(0.0), phase);
+    }
+}
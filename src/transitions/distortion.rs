@@ -1,14 +1,74 @@
+use std::f32::consts::PI;
+use std::str::FromStr;
+use std::time::Duration;
+
 use crate::ggez_utils::Point2;
 use crevice::std140::AsStd140;
 use ggez::graphics::{Canvas, DrawParam, Drawable, Image};
 use ggez::*;
 
+use crate::sync_timer::{Clock, PlaybackClock};
+use crate::transitions::params::TransitionParams;
+use crate::transitions::shaders::Shaders;
 use crate::transitions::transition::Transition;
 
+/// The direction the wave travels along.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WaveDirection {
+    /// the wave travels horizontally, the image is displaced vertically
+    Horizontal,
+    /// the wave travels vertically, the image is displaced horizontally
+    Vertical,
+    /// a ripple from the center of the image
+    Radial,
+}
+
+impl WaveDirection {
+    /// The value of the direction in the shader.
+    fn to_uniform(self) -> u32 {
+        match self {
+            WaveDirection::Horizontal => 0,
+            WaveDirection::Vertical => 1,
+            WaveDirection::Radial => 2,
+        }
+    }
+}
+
+impl FromStr for WaveDirection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "horizontal" => Ok(WaveDirection::Horizontal),
+            "vertical" => Ok(WaveDirection::Vertical),
+            "radial" => Ok(WaveDirection::Radial),
+            _ => Err("expected one of horizontal, vertical, radial".to_string()),
+        }
+    }
+}
+
+/// The phase of the wave at the start, in radians.
+const START_PHASE: f32 = 30.0 * PI / 180.0;
+
+/// The phase of the wave after the given seconds, it moves by a whole wave every 1 / speed
+/// seconds.
+fn wave_phase(speed: f32, time: f32) -> f32 {
+    START_PHASE - 2.0 * PI * speed * time
+}
+
 // Define the input struct for our shader.
-#[derive(AsStd140, Clone)]
-pub struct Dim {
+#[derive(AsStd140)]
+struct Dim {
+    /// from 1 to 0, the wave settles when it reaches 0
     rate: f32,
+    /// the maximum displacement, relative to the size of the image
+    amplitude: f32,
+    /// the number of waves across the image
+    frequency: f32,
+    /// the phase of the wave, in radians
+    phase: f32,
+    aspect_ratio: f32,
+    direction: u32,
 }
 
 pub struct Distortion {
@@ -16,17 +76,60 @@ pub struct Distortion {
     ended: bool,
     shader: Option<graphics::Shader>,
     dim: Dim,
+    /// the number of waves the wave travels per second
+    speed: f32,
+    clock: PlaybackClock,
+    /// the time of the clock when the transition has started
+    start: Duration,
 }
 
 impl Distortion {
-    pub fn new() -> Distortion {
+    /// amplitude is relative to the size of the image, frequency is the number of waves across
+    /// the image, speed the number of waves the wave travels per second.
+    pub fn new(amplitude: f32, frequency: f32, speed: f32, direction: WaveDirection) -> Distortion {
         Distortion {
             image: None,
             ended: true,
             shader: None,
-            dim: Dim { rate: 1.0 },
+            dim: Dim {
+                rate: 1.0,
+                amplitude,
+                frequency,
+                phase: START_PHASE,
+                aspect_ratio: 1.0,
+                direction: direction.to_uniform(),
+            },
+            speed,
+            clock: PlaybackClock::new(),
+            start: Duration::ZERO,
         }
     }
+
+    /// Parameters:
+    /// - amplitude: the maximum displacement, relative to the size of the image (default 0.3)
+    /// - frequency: the number of waves across the image (default 5)
+    /// - direction: horizontal (default), vertical, radial
+    /// - speed: the number of waves the wave travels per second (default 0)
+    pub fn from_params(params: &TransitionParams) -> GameResult<Distortion> {
+        Ok(Distortion::new(
+            params.get("amplitude", 0.3)?,
+            params.get("frequency", 5.0)?,
+            params.get("speed", 0.0)?,
+            params.get("direction", WaveDirection::Horizontal)?,
+        ))
+    }
+
+    /// The seconds since the start of the transition, by the clock of the slideshow.
+    fn elapsed(&self) -> f32 {
+        self.clock.now().saturating_sub(self.start).as_secs_f32()
+    }
+
+    fn restart(&mut self) {
+        self.ended = false;
+        self.dim.rate = 1.0;
+        self.dim.phase = START_PHASE;
+        self.start = self.clock.now();
+    }
 }
 
 impl Transition for Distortion {
@@ -35,25 +138,20 @@ impl Transition for Distortion {
             if self.dim.rate <= 0.0 {
                 self.ended = true;
             } else {
-                match &self.image {
-                    Some(image) => {
-                        //println!("Distortion 1 {:?}.", SystemTime::now());
-                        let param = DrawParam::new().dest(Point2::new(0.0, 0.0));
+                self.dim.phase = wave_phase(self.speed, self.elapsed());
 
-                        if let Some(ref shader) = self.shader {
-                            //println!("Distortion 3 {:?}.", SystemTime::now());
+                if let Some(image) = &self.image {
+                    let param = DrawParam::new().dest(Point2::new(0.0, 0.0));
 
-                            let _lock = canvas.set_shader(shader);
+                    if let Some(ref shader) = self.shader {
+                        canvas.set_shader(shader);
 
-                            let shader_params =
-                                graphics::ShaderParamsBuilder::new(&self.dim).build(ctx);
-                            canvas.set_shader_params(&shader_params);
+                        let shader_params =
+                            graphics::ShaderParamsBuilder::new(&self.dim).build(ctx);
+                        canvas.set_shader_params(&shader_params);
 
-                            image.draw(canvas, param);
-                            //println!("Distortion 6 {:?}.", SystemTime::now());
-                        }
+                        image.draw(canvas, param);
                     }
-                    None => {}
                 }
                 self.dim.rate -= 0.01;
             }
@@ -62,18 +160,114 @@ impl Transition for Distortion {
         Ok(!self.ended)
     }
 
-    fn update_image(&mut self, ctx: &mut Context, image: Image) {
-        let dim = Dim { rate: 0.5 };
-        let shader = graphics::ShaderBuilder::new()
-            .vertex_path("/simple.vert.wgsl")
-            .fragment_path("/distortion.frag.wgsl")
-            //.fragment_path("/simple.frag.wgsl")
-            .build(ctx)
-            .unwrap();
-        self.shader = Some(shader);
-        self.dim = dim;
+    fn set_clock(&mut self, clock: PlaybackClock) {
+        self.clock = clock;
+    }
+
+    fn prepare(&mut self, ctx: &mut Context, shaders: &mut Shaders) -> GameResult {
+        self.shader = Some(shaders.get(ctx, "/distortion.frag.wgsl")?);
+        Ok(())
+    }
+
+    fn skip_to_end(&mut self, _ctx: &mut Context) -> GameResult {
+        self.ended = true;
+        Ok(())
+    }
+
+    fn update_image(&mut self, _ctx: &mut Context, image: Image) {
+        self.dim.aspect_ratio = image.width() as f32 / image.height() as f32;
         self.image = Some(image);
-        self.ended = false;
-        self.dim.rate = 1.0;
+        self.restart();
+    }
+
+    fn resize(&mut self, _ctx: &mut Context, image: Image) {
+        self.dim.aspect_ratio = image.width() as f32 / image.height() as f32;
+        self.image = Some(image);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sync_timer::ManualClock;
+
+    use super::*;
+
+    fn params(spec: &str) -> TransitionParams {
+        TransitionParams::parse(spec).unwrap()
+    }
+
+    #[test]
+    fn test_default_params() {
+        let distortion = Distortion::from_params(&params("distortion")).unwrap();
+
+        assert_eq!(0.3, distortion.dim.amplitude);
+        assert_eq!(5.0, distortion.dim.frequency);
+        assert_eq!(0.0, distortion.speed);
+        assert_eq!(
+            WaveDirection::Horizontal.to_uniform(),
+            distortion.dim.direction
+        );
+    }
+
+    #[test]
+    fn test_params() {
+        let distortion = Distortion::from_params(&params(
+            "distortion:amplitude=0.1,frequency=3,speed=2,direction=radial",
+        ))
+        .unwrap();
+
+        assert_eq!(0.1, distortion.dim.amplitude);
+        assert_eq!(3.0, distortion.dim.frequency);
+        assert_eq!(2.0, distortion.speed);
+        assert_eq!(2, distortion.dim.direction);
+    }
+
+    #[test]
+    fn test_directions() {
+        for (name, uniform) in [("horizontal", 0), ("vertical", 1), ("radial", 2)] {
+            let spec = format!("distortion:direction={}", name);
+            let distortion = Distortion::from_params(&params(&spec)).unwrap();
+            assert_eq!(uniform, distortion.dim.direction);
+        }
+    }
+
+    #[test]
+    fn test_invalid_params() {
+        assert!(Distortion::from_params(&params("distortion:direction=diagonal")).is_err());
+        assert!(Distortion::from_params(&params("distortion:amplitude=high")).is_err());
+        assert!(Distortion::from_params(&params("distortion:speed=")).is_err());
+    }
+
+    #[test]
+    fn test_wave_phase() {
+        assert_eq!(START_PHASE, wave_phase(0.0, 10.0));
+        assert_eq!(START_PHASE, wave_phase(2.0, 0.0));
+        assert!((wave_phase(1.0, 0.5) - (START_PHASE - PI)).abs() < 1e-5);
+        // a whole wave per second, after a second it's in the same place
+        let phase = wave_phase(1.0, 1.0) + 2.0 * PI;
+        assert!((phase - START_PHASE).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_time_follows_the_clock() {
+        let source = ManualClock::new();
+        let clock = PlaybackClock::with_source(Box::new(source.clone()) as Box<dyn Clock>);
+        let mut distortion = Distortion::new(0.3, 5.0, 1.0, WaveDirection::Horizontal);
+        distortion.set_clock(clock.clone());
+
+        source.advance(Duration::from_secs(3));
+        distortion.restart();
+        source.advance(Duration::from_millis(250));
+        assert_eq!(0.25, distortion.elapsed());
+
+        // while the slideshow is paused the wave stops
+        clock.pause();
+        source.advance(Duration::from_secs(1));
+        assert_eq!(0.25, distortion.elapsed());
+
+        clock.resume();
+        clock.set_scale(2.0);
+        source.advance(Duration::from_millis(250));
+        assert_eq!(0.75, distortion.elapsed());
     }
 }
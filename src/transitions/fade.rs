@@ -1,7 +1,7 @@
 use ggez::graphics::{Canvas, DrawParam, Drawable, Image, ImageFormat};
 use ggez::*;
+use image::imageops::CatmullRom;
 use image::RgbaImage;
-use std::time::SystemTime;
 
 use crate::ggez_utils::Point2;
 use crate::transitions::transition::*;
@@ -13,7 +13,6 @@ pub struct Fade {
     last_image: Option<RgbaImage>,
     ended: bool,
     alpha: f32,
-    time: SystemTime,
 }
 
 impl Fade {
@@ -23,11 +22,16 @@ impl Fade {
             last_image: None,
             ended: true,
             alpha: 0.0,
-            time: SystemTime::now(),
         }
     }
 }
 
+/// The pixels of the image, the fade blends them on the CPU.
+fn to_rgba(ctx: &mut Context, image: &Image) -> RgbaImage {
+    let pixels = image.to_pixels(ctx).unwrap();
+    RgbaImage::from_raw(image.width(), image.height(), pixels).unwrap()
+}
+
 impl Fade {
     fn blend(&self, from: u8, to: u8) -> u8 {
         let f = from as f32 * (255.0 - self.alpha) / 255.0;
@@ -39,59 +43,56 @@ impl Fade {
 impl Transition for Fade {
     fn draw(&mut self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult<bool> {
         if !self.ended {
-            match &self.image {
-                Some(image) => {
-                    let mut ii = image.clone();
-
-                    match &self.last_image {
-                        Some(last_image) => {
-                            for x in 0..ii.width() {
-                                for y in 0..ii.height() {
-                                    let color = ii.get_pixel_mut(x, y);
-                                    let last_color = last_image.get_pixel(x, y);
-                                    color[0] = self.blend(last_color[0], color[0]);
-                                    color[1] = self.blend(last_color[1], color[1]);
-                                    color[2] = self.blend(last_color[2], color[2]);
-                                }
+            if let Some(image) = &self.image {
+                let mut ii = image.clone();
+
+                match &self.last_image {
+                    Some(last_image) => {
+                        for x in 0..ii.width() {
+                            for y in 0..ii.height() {
+                                let color = ii.get_pixel_mut(x, y);
+                                let last_color = last_image.get_pixel(x, y);
+                                color[0] = self.blend(last_color[0], color[0]);
+                                color[1] = self.blend(last_color[1], color[1]);
+                                color[2] = self.blend(last_color[2], color[2]);
                             }
+                        }
 
-                            let i = Image::from_pixels(
-                                ctx,
-                                ii.as_raw(),
-                                ImageFormat::Rgba8UnormSrgb,
-                                image.width(),
-                                image.height(),
-                            );
+                        let i = Image::from_pixels(
+                            ctx,
+                            ii.as_raw(),
+                            ImageFormat::Rgba8UnormSrgb,
+                            image.width(),
+                            image.height(),
+                        );
 
-                            let draw_param = DrawParam::default();
-                            draw_param.dest(Point2::new(0.0, 0.0));
+                        let draw_param = DrawParam::default();
+                        draw_param.dest(Point2::new(0.0, 0.0));
 
-                            i.draw(canvas, draw_param);
-                        }
-                        None => {
-                            for x in 0..ii.width() {
-                                for y in 0..ii.height() {
-                                    let color = ii.get_pixel_mut(x, y);
-                                    color[0] = self.blend(0, color[0]);
-                                    color[1] = self.blend(0, color[1]);
-                                    color[2] = self.blend(0, color[2]);
-                                }
+                        i.draw(canvas, draw_param);
+                    }
+                    None => {
+                        for x in 0..ii.width() {
+                            for y in 0..ii.height() {
+                                let color = ii.get_pixel_mut(x, y);
+                                color[0] = self.blend(0, color[0]);
+                                color[1] = self.blend(0, color[1]);
+                                color[2] = self.blend(0, color[2]);
                             }
-                            let i = Image::from_pixels(
-                                ctx,
-                                ii.as_raw(),
-                                ImageFormat::Rgba8UnormSrgb,
-                                image.width(),
-                                image.height(),
-                            );
-                            let draw_param = DrawParam::default();
-                            draw_param.dest(Point2::new(0.0, 0.0));
-
-                            i.draw(canvas, draw_param);
                         }
+                        let i = Image::from_pixels(
+                            ctx,
+                            ii.as_raw(),
+                            ImageFormat::Rgba8UnormSrgb,
+                            image.width(),
+                            image.height(),
+                        );
+                        let draw_param = DrawParam::default();
+                        draw_param.dest(Point2::new(0.0, 0.0));
+
+                        i.draw(canvas, draw_param);
                     }
                 }
-                None => {}
             }
             self.alpha += VELOCITY;
             if self.alpha > 255.0 {
@@ -103,13 +104,36 @@ impl Transition for Fade {
         Ok(!self.ended)
     }
 
+    fn skip_to_end(&mut self, _ctx: &mut Context) -> GameResult {
+        self.alpha = 0.0;
+        self.ended = true;
+        Ok(())
+    }
+
     fn update_image(&mut self, ctx: &mut Context, image: Image) {
         self.last_image = self.image.clone();
+        self.image = Some(to_rgba(ctx, &image));
+        self.ended = false;
+    }
 
-        let pixels = image.to_pixels(ctx).unwrap();
-        let i = RgbaImage::from_raw(image.width(), image.height(), pixels).unwrap();
-        self.image = Some(i);
+    fn set_images(&mut self, ctx: &mut Context, from: Image, to: Image) {
+        let mut last_image = to_rgba(ctx, &from);
+        if last_image.dimensions() != (to.width(), to.height()) {
+            last_image = image::imageops::resize(&last_image, to.width(), to.height(), CatmullRom);
+        }
 
+        self.last_image = Some(last_image);
+        self.image = Some(to_rgba(ctx, &to));
         self.ended = false;
     }
+
+    fn resize(&mut self, ctx: &mut Context, image: Image) {
+        // the fade goes on, but the last image must have the same size of the new one
+        self.last_image = self.last_image.take().map(|last_image| {
+            image::imageops::resize(&last_image, image.width(), image.height(), CatmullRom)
+        });
+
+        let pixels = image.to_pixels(ctx).unwrap();
+        self.image = RgbaImage::from_raw(image.width(), image.height(), pixels);
+    }
 }
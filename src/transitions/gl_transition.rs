@@ -0,0 +1,499 @@
+use std::io::Read;
+
+use bytemuck::{Pod, Zeroable};
+use crevice::std140::Std140;
+use ggez::graphics::{Canvas, DrawParam, Drawable, Image, Sampler};
+use ggez::*;
+
+use crate::ggez_utils::Point2;
+use crate::transitions::params::TransitionParams;
+use crate::transitions::shaders::Shaders;
+use crate::transitions::transition::{ImagePair, Transition};
+
+/// how much the progress advances every frame
+const STEP: f32 = 0.01;
+/// the maximum number of uniforms a transition can declare
+pub(crate) const MAX_UNIFORMS: usize = 16;
+
+/// The type of a uniform declared by a gl-transition, every uniform is stored in a vec4.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum GlUniformType {
+    Float,
+    Vec2,
+    Vec3,
+    Vec4,
+    Int,
+    IVec2,
+    Bool,
+}
+
+impl GlUniformType {
+    fn parse(name: &str) -> Option<GlUniformType> {
+        match name {
+            "float" => Some(GlUniformType::Float),
+            "vec2" => Some(GlUniformType::Vec2),
+            "vec3" => Some(GlUniformType::Vec3),
+            "vec4" => Some(GlUniformType::Vec4),
+            "int" => Some(GlUniformType::Int),
+            "ivec2" => Some(GlUniformType::IVec2),
+            "bool" => Some(GlUniformType::Bool),
+            _ => None,
+        }
+    }
+
+    fn components(self) -> usize {
+        match self {
+            GlUniformType::Float | GlUniformType::Int | GlUniformType::Bool => 1,
+            GlUniformType::Vec2 | GlUniformType::IVec2 => 2,
+            GlUniformType::Vec3 => 3,
+            GlUniformType::Vec4 => 4,
+        }
+    }
+
+    /// The GLSL expression that reads the uniform from its slot.
+    fn read_slot(self, slot: usize) -> String {
+        match self {
+            GlUniformType::Float => format!("(slots[{}].x)", slot),
+            GlUniformType::Vec2 => format!("(slots[{}].xy)", slot),
+            GlUniformType::Vec3 => format!("(slots[{}].xyz)", slot),
+            GlUniformType::Vec4 => format!("(slots[{}])", slot),
+            GlUniformType::Int => format!("int(slots[{}].x)", slot),
+            GlUniformType::IVec2 => format!("ivec2(slots[{}].xy)", slot),
+            GlUniformType::Bool => format!("(slots[{}].x != 0.0)", slot),
+        }
+    }
+
+    /// Parses a value, the components of vectors are separated by ':', like 1.0:-1.0.
+    fn parse_value(self, value: &str) -> Result<[f32; 4], String> {
+        let components = if self == GlUniformType::Bool {
+            match value {
+                "true" => vec![1.0],
+                "false" => vec![0.0],
+                _ => return Err("expected true or false".to_string()),
+            }
+        } else {
+            value
+                .split(':')
+                .map(|it| it.trim().parse::<f32>().map_err(|e| e.to_string()))
+                .collect::<Result<Vec<_>, _>>()?
+        };
+
+        to_components(self.components(), &components)
+    }
+}
+
+/// Fills the components of the uniform, a single value is repeated like in vec2(0.5).
+pub(crate) fn to_components(components: usize, values: &[f32]) -> Result<[f32; 4], String> {
+    let mut result = [0.0; 4];
+
+    match values.len() {
+        1 => result[..components].fill(values[0]),
+        n if n == components => result[..n].copy_from_slice(values),
+        _ => {
+            return Err(format!(
+                "expected {} components separated by ':'",
+                components
+            ))
+        }
+    }
+
+    Ok(result)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct GlUniform {
+    name: String,
+    uniform_type: GlUniformType,
+    default: [f32; 4],
+}
+
+/// A transition in the format of https://gl-transitions.com/: a GLSL function
+/// `vec4 transition(vec2 uv)` using `getFromColor`, `getToColor`, `progress` and `ratio`, with
+/// uniforms declared like `uniform float smoothness; // = 0.5`.
+#[derive(Debug, Clone, PartialEq)]
+struct GlTransitionSource {
+    uniforms: Vec<GlUniform>,
+    /// the code without the uniform declarations
+    body: String,
+}
+
+impl GlTransitionSource {
+    fn parse(source: &str) -> Result<GlTransitionSource, String> {
+        let mut uniforms = Vec::new();
+        let mut body = String::new();
+
+        for line in source.lines() {
+            match line.trim().strip_prefix("uniform ") {
+                Some(declaration) => uniforms.push(GlTransitionSource::parse_uniform(declaration)?),
+                None => {
+                    body.push_str(line);
+                    body.push('\n');
+                }
+            }
+        }
+
+        if uniforms.len() > MAX_UNIFORMS {
+            return Err(format!("more than {} uniforms", MAX_UNIFORMS));
+        }
+
+        Ok(GlTransitionSource { uniforms, body })
+    }
+
+    /// Parses a declaration like `float smoothness; // = 0.5`.
+    fn parse_uniform(declaration: &str) -> Result<GlUniform, String> {
+        let (declaration, comment) = match declaration.split_once("//") {
+            Some((declaration, comment)) => (declaration, Some(comment)),
+            None => (declaration, None),
+        };
+
+        let mut tokens = declaration.trim().trim_end_matches(';').split_whitespace();
+
+        let (type_name, name) = match (tokens.next(), tokens.next(), tokens.next()) {
+            (Some(type_name), Some(name), None) => (type_name, name),
+            _ => return Err(format!("unsupported uniform declaration '{}'", declaration)),
+        };
+
+        let uniform_type = GlUniformType::parse(type_name)
+            .ok_or_else(|| format!("unsupported type {} of uniform {}", type_name, name))?;
+
+        let default = match comment.and_then(|it| it.trim().strip_prefix('=')) {
+            Some(value) => GlTransitionSource::parse_default(uniform_type, value.trim())
+                .map_err(|e| format!("invalid default value of uniform {}: {}", name, e))?,
+            None => [0.0; 4],
+        };
+
+        Ok(GlUniform {
+            name: name.to_string(),
+            uniform_type,
+            default,
+        })
+    }
+
+    /// Parses a GLSL literal like `0.5`, `true` or `vec2(1.0, -1.0)`.
+    fn parse_default(uniform_type: GlUniformType, value: &str) -> Result<[f32; 4], String> {
+        let value = value.trim_end_matches(';').trim();
+
+        let arguments = match value.split_once('(') {
+            Some((_, arguments)) => arguments.trim_end_matches(')'),
+            None => value,
+        };
+
+        let values = arguments
+            .split(',')
+            .map(|it| match it.trim() {
+                "true" => Ok(1.0),
+                "false" => Ok(0.0),
+                number => number.parse::<f32>().map_err(|e| e.to_string()),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        to_components(uniform_type.components(), &values)
+    }
+
+    /// Wraps the transition in a fragment shader with the bindings that ggez expects: the new
+    /// image is the one that is drawn, the old one is passed as a shader parameter.
+    fn to_glsl(&self) -> String {
+        let mut glsl = String::from(
+            "#version 450
+
+layout(location = 0) in vec2 v_uv;
+layout(location = 0) out vec4 o_color;
+
+layout(set = 1, binding = 0) uniform texture2D t_to;
+layout(set = 1, binding = 1) uniform sampler s_to;
+
+layout(set = 3, binding = 0) uniform GlTransitionUniforms {
+    float progress;
+    float ratio;
+    vec4 slots[16];
+};
+
+layout(set = 3, binding = 1) uniform texture2D t_from;
+layout(set = 3, binding = 2) uniform sampler s_from;
+
+// gl-transitions have the origin at the bottom left corner
+vec4 getFromColor(vec2 uv) {
+    return textureLod(sampler2D(t_from, s_from), vec2(uv.x, 1.0 - uv.y), 0.0);
+}
+
+vec4 getToColor(vec2 uv) {
+    return textureLod(sampler2D(t_to, s_to), vec2(uv.x, 1.0 - uv.y), 0.0);
+}
+
+",
+        );
+
+        for (slot, uniform) in self.uniforms.iter().enumerate() {
+            glsl.push_str(&format!(
+                "#define {} {}\n",
+                uniform.name,
+                uniform.uniform_type.read_slot(slot)
+            ));
+        }
+
+        glsl.push_str(&self.body);
+
+        glsl.push_str(
+            "
+void main() {
+    o_color = transition(vec2(v_uv.x, 1.0 - v_uv.y));
+}
+",
+        );
+
+        glsl
+    }
+
+    /// Translates the transition to a WGSL fragment shader, with naga.
+    fn to_wgsl(&self) -> Result<String, String> {
+        let glsl = self.to_glsl();
+
+        let mut parser = naga::front::glsl::Frontend::default();
+        let mut module = parser
+            .parse(
+                &naga::front::glsl::Options::from(naga::ShaderStage::Fragment),
+                &glsl,
+            )
+            .map_err(|errors| {
+                errors
+                    .iter()
+                    .map(|e| e.to_string())
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            })?;
+
+        // ggez expects this name for the fragment entry point
+        for entry_point in module.entry_points.iter_mut() {
+            entry_point.name = "fs_main".to_string();
+        }
+
+        let info = naga::valid::Validator::new(
+            naga::valid::ValidationFlags::all(),
+            naga::valid::Capabilities::empty(),
+        )
+        .validate(&module)
+        .map_err(|e| format!("{:?}", e))?;
+
+        naga::back::wgsl::write_string(&module, &info, naga::back::wgsl::WriterFlags::empty())
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// The uniforms of the wrapper shader, the layout is the std140 one of GlTransitionUniforms,
+/// that cannot be derived since the uniforms depend on the transition.
+/// It's shared with the user defined WGSL transitions, that have the same layout.
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub(crate) struct Dim {
+    pub(crate) progress: f32,
+    pub(crate) ratio: f32,
+    padding: [f32; 2],
+    pub(crate) slots: [[f32; 4]; MAX_UNIFORMS],
+}
+
+unsafe impl Std140 for Dim {
+    const ALIGNMENT: usize = 16;
+}
+
+/// A transition loaded from a gl-transitions GLSL file.
+pub struct GlTransition {
+    path: String,
+    wgsl: String,
+    shader: Option<graphics::Shader>,
+    images: ImagePair,
+    ended: bool,
+    dim: Dim,
+}
+
+impl GlTransition {
+    /// Parameters:
+    /// - name: the name of a bundled transition in /gl-transitions, or the path of a GLSL file in
+    ///   the resources, like /gl-transitions/crosswarp.glsl
+    /// - the uniforms declared by the transition, the components of vectors are separated by ':'
+    pub fn from_params(ctx: &mut Context, params: &TransitionParams) -> GameResult<GlTransition> {
+        let name = params.get_opt::<String>("name")?.ok_or_else(|| {
+            GameError::CustomError("the name of the gl-transition is mandatory".to_string())
+        })?;
+
+        let path = if name.starts_with('/') {
+            name
+        } else {
+            format!("/gl-transitions/{}.glsl", name)
+        };
+
+        let mut source = String::new();
+        ctx.fs.open(&path)?.read_to_string(&mut source)?;
+
+        let error = |e: String| GameError::ResourceLoadError(format!("{}: {}", path, e));
+
+        let source = GlTransitionSource::parse(&source).map_err(error)?;
+        let wgsl = source.to_wgsl().map_err(error)?;
+
+        let mut dim = Dim::zeroed();
+
+        for (slot, uniform) in source.uniforms.iter().enumerate() {
+            dim.slots[slot] = match params.get_opt::<String>(&uniform.name)? {
+                Some(value) => uniform.uniform_type.parse_value(&value).map_err(|e| {
+                    GameError::CustomError(format!(
+                        "Invalid value '{}' for parameter {} of {}: {}",
+                        value, uniform.name, path, e
+                    ))
+                })?,
+                None => uniform.default,
+            };
+        }
+
+        Ok(GlTransition {
+            path,
+            wgsl,
+            shader: None,
+            images: ImagePair::default(),
+            ended: true,
+            dim,
+        })
+    }
+
+    /// Draws the frame at the progress in the parameters of the shader.
+    fn draw_frame(&self, ctx: &mut Context, canvas: &mut Canvas) {
+        if let (Some(image), Some(last_image), Some(shader)) =
+            (self.images.image(), self.images.last_image(), &self.shader)
+        {
+            canvas.set_shader(shader);
+            let shader_params = graphics::ShaderParamsBuilder::new(&self.dim)
+                .images(&[last_image], &[Sampler::linear_clamp()], false)
+                .build(ctx);
+            canvas.set_shader_params(&shader_params);
+
+            image.draw(canvas, DrawParam::new().dest(Point2::new(0.0, 0.0)));
+            canvas.set_default_shader();
+        }
+    }
+}
+
+impl Transition for GlTransition {
+    fn draw(&mut self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult<bool> {
+        if self.ended {
+            return Ok(false);
+        }
+
+        self.dim.progress = (self.dim.progress + STEP).min(1.0);
+
+        self.draw_frame(ctx, canvas);
+
+        if self.dim.progress >= 1.0 {
+            self.ended = true;
+        }
+
+        Ok(!self.ended)
+    }
+
+    fn reversible(&self) -> bool {
+        true
+    }
+
+    fn draw_at(&mut self, ctx: &mut Context, canvas: &mut Canvas, progress: f32) -> GameResult {
+        self.dim.progress = progress;
+        self.draw_frame(ctx, canvas);
+        Ok(())
+    }
+
+    fn prepare(&mut self, ctx: &mut Context, shaders: &mut Shaders) -> GameResult {
+        self.shader = Some(shaders.get_code(ctx, &self.path, &self.wgsl)?);
+        Ok(())
+    }
+
+    fn skip_to_end(&mut self, _ctx: &mut Context) -> GameResult {
+        self.ended = true;
+        Ok(())
+    }
+
+    fn update_image(&mut self, ctx: &mut Context, image: Image) {
+        let from = self.images.next_from(ctx, &image);
+        self.set_images(ctx, from, image);
+    }
+
+    fn set_images(&mut self, _ctx: &mut Context, from: Image, to: Image) {
+        self.dim.ratio = to.width() as f32 / to.height() as f32;
+        self.images.set(from, to);
+        self.ended = false;
+        self.dim.progress = 0.0;
+    }
+
+    fn resize(&mut self, _ctx: &mut Context, image: Image) {
+        self.dim.ratio = image.width() as f32 / image.height() as f32;
+        self.images.resize(image);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIRECTIONAL_WIPE: &str =
+        include_str!("../../resources/gl-transitions/directionalwipe.glsl");
+
+    #[test]
+    fn test_parse_uniforms() {
+        let source = GlTransitionSource::parse(DIRECTIONAL_WIPE).unwrap();
+
+        assert_eq!(
+            vec![
+                GlUniform {
+                    name: "direction".to_string(),
+                    uniform_type: GlUniformType::Vec2,
+                    default: [1.0, -1.0, 0.0, 0.0],
+                },
+                GlUniform {
+                    name: "smoothness".to_string(),
+                    uniform_type: GlUniformType::Float,
+                    default: [0.5, 0.0, 0.0, 0.0],
+                },
+            ],
+            source.uniforms
+        );
+        assert!(!source.body.contains("uniform"));
+    }
+
+    #[test]
+    fn test_parse_default() {
+        assert_eq!(
+            [0.5, 0.5, 0.0, 0.0],
+            GlTransitionSource::parse_default(GlUniformType::Vec2, "vec2(0.5)").unwrap()
+        );
+        assert_eq!(
+            [1.0, 0.0, 0.0, 0.0],
+            GlTransitionSource::parse_default(GlUniformType::Bool, "true").unwrap()
+        );
+        assert!(GlTransitionSource::parse_default(GlUniformType::Vec3, "vec3(1.0, 2.0)").is_err());
+    }
+
+    #[test]
+    fn test_parse_value() {
+        assert_eq!(
+            [1.0, -1.0, 0.0, 0.0],
+            GlUniformType::Vec2.parse_value("1:-1").unwrap()
+        );
+        assert!(GlUniformType::Float.parse_value("many").is_err());
+    }
+
+    #[test]
+    fn test_unsupported_uniform() {
+        assert!(GlTransitionSource::parse("uniform sampler2D displacementMap;").is_err());
+    }
+
+    #[test]
+    fn test_bundled_transitions_translate_to_wgsl() {
+        let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("resources/gl-transitions");
+
+        for entry in dir.read_dir().unwrap() {
+            let path = entry.unwrap().path();
+            let source = std::fs::read_to_string(&path).unwrap();
+
+            let wgsl = GlTransitionSource::parse(&source)
+                .and_then(|it| it.to_wgsl())
+                .unwrap_or_else(|e| panic!("{:?}: {}", path, e));
+
+            assert!(wgsl.contains("fn fs_main("), "{:?}", path);
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use crevice::std140::AsStd140;
+use ggez::*;
+
+use crate::transitions::params::TransitionParams;
+use crate::transitions::shader_transition::{ShaderDim, ShaderTransition};
+use crate::velocity::*;
+
+// Define the input struct for our shader, public for the type of the transition, but it can be
+// built only here.
+#[derive(AsStd140)]
+pub struct KaleidoscopeDim {
+    progress: f32,
+    segments: f32,
+    rotation: f32,
+    aspect_ratio: f32,
+}
+
+impl ShaderDim for KaleidoscopeDim {
+    fn set_progress(&mut self, progress: f32) {
+        self.progress = progress;
+    }
+
+    fn set_aspect_ratio(&mut self, aspect_ratio: f32) {
+        self.aspect_ratio = aspect_ratio;
+    }
+}
+
+/// The old image folds in the slices of a kaleidoscope, that turns and unfolds in the new one.
+pub type Kaleidoscope = ShaderTransition<KaleidoscopeDim>;
+
+impl Kaleidoscope {
+    /// segments is the number of mirrored slices, rotation is the number of turns of the
+    /// kaleidoscope during the transition.
+    pub fn new(segments: u32, rotation: f32) -> Kaleidoscope {
+        let dim = KaleidoscopeDim {
+            progress: 0.0,
+            segments: segments.max(2) as f32,
+            rotation,
+            aspect_ratio: 1.0,
+        };
+
+        // it lingers on the complete kaleidoscope, in the middle
+        ShaderTransition::with_shader(
+            "/kaleidoscope.frag.wgsl",
+            dim,
+            Box::new(StepsVelocity::new(vec![1.5, 0.6, 1.5])),
+        )
+    }
+
+    /// Parameters:
+    /// - segments: the number of mirrored slices (default 6)
+    /// - rotation: the number of turns during the transition (default 0.5)
+    /// - easing: the easing curve, see Easing (default slow in the middle)
+    pub fn from_params(params: &TransitionParams) -> GameResult<Kaleidoscope> {
+        Kaleidoscope::new(params.get("segments", 6)?, params.get("rotation", 0.5)?)
+            .with_params(params)
+    }
+}
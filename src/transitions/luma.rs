@@ -0,0 +1,191 @@
+use std::io::Read;
+
+use crevice::std140::AsStd140;
+use ggez::graphics::{Canvas, Color, DrawMode, DrawParam, Drawable, Image, ImageFormat, Sampler};
+use ggez::*;
+
+use crate::ggez_utils::{draw_rect, Point2};
+use crate::transitions::params::TransitionParams;
+use crate::transitions::shaders::Shaders;
+use crate::transitions::transition::Transition;
+
+/// how much the progress advances every frame
+const STEP: f32 = 0.01;
+
+// Define the input struct for our shader.
+#[derive(AsStd140)]
+struct Dim {
+    progress: f32,
+    softness: f32,
+    invert: u32,
+}
+
+/// A "luma wipe": the new image is revealed where the luminance of a grayscale mask is lower
+/// than the progress, so dark areas of the mask are revealed first.
+pub struct Luma {
+    mask_path: String,
+    mask: Option<Image>,
+    image: Option<Image>,
+    last_image: Option<Image>,
+    ended: bool,
+    shader: Option<graphics::Shader>,
+    dim: Dim,
+}
+
+impl Luma {
+    /// mask_path is the path of the mask in the resources, softness is the width of the
+    /// transition between revealed and hidden areas, in luminance.
+    pub fn new(mask_path: String, softness: f32, invert: bool) -> Luma {
+        Luma {
+            mask_path,
+            mask: None,
+            image: None,
+            last_image: None,
+            ended: true,
+            shader: None,
+            dim: Dim {
+                progress: 0.0,
+                softness: softness.max(0.0),
+                invert: u32::from(invert),
+            },
+        }
+    }
+
+    /// Parameters:
+    /// - mask: one of the bundled masks: clock (default), radial, barn_door, noise, or the path of
+    ///   an image in the resources, like /masks/clock.png
+    /// - softness: the width of the edge between the images, in luminance (default 0.1)
+    /// - invert: true to reveal the bright areas first (default false)
+    pub fn from_params(params: &TransitionParams) -> GameResult<Luma> {
+        let mask = params.get("mask", "clock".to_string())?;
+
+        let mask_path = if mask.starts_with('/') {
+            mask
+        } else {
+            format!("/masks/{}.png", mask)
+        };
+
+        Ok(Luma::new(
+            mask_path,
+            params.get("softness", 0.1)?,
+            params.get("invert", false)?,
+        ))
+    }
+
+    /// The mask is loaded as linear values, with the luminance in every channel, otherwise
+    /// an sRGB texture would alter the timing of the wipe.
+    fn load_mask(&self, ctx: &mut Context) -> GameResult<Image> {
+        let mut bytes = Vec::new();
+        ctx.fs.open(&self.mask_path)?.read_to_end(&mut bytes)?;
+
+        let mask = image::load_from_memory(&bytes)
+            .map_err(|e| GameError::ResourceLoadError(format!("{}: {}", self.mask_path, e)))?
+            .to_luma8();
+
+        let pixels = mask
+            .pixels()
+            .flat_map(|pixel| [pixel[0], pixel[0], pixel[0], 255])
+            .collect::<Vec<_>>();
+
+        Ok(Image::from_pixels(
+            ctx,
+            &pixels,
+            ImageFormat::Rgba8Unorm,
+            mask.width(),
+            mask.height(),
+        ))
+    }
+
+    /// Draws the frame at the progress in the parameters of the shader.
+    fn draw_frame(&self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
+        if let (Some(image), Some(mask), Some(shader)) = (&self.image, &self.mask, &self.shader) {
+            // the old image is drawn every frame, since the new one is blended over it
+            match &self.last_image {
+                Some(last_image) => {
+                    let param = DrawParam::new().dest(Point2::new(0.0, 0.0)).scale([
+                        image.width() as f32 / last_image.width() as f32,
+                        image.height() as f32 / last_image.height() as f32,
+                    ]);
+                    last_image.draw(canvas, param);
+                }
+                None => draw_rect(
+                    ctx,
+                    canvas,
+                    0.0,
+                    0.0,
+                    image.width() as f32,
+                    image.height() as f32,
+                    &Color::BLACK,
+                    DrawMode::fill(),
+                )?,
+            }
+
+            canvas.set_shader(shader);
+            let shader_params = graphics::ShaderParamsBuilder::new(&self.dim)
+                .images(&[mask], &[Sampler::linear_clamp()], false)
+                .build(ctx);
+            canvas.set_shader_params(&shader_params);
+
+            image.draw(canvas, DrawParam::new().dest(Point2::new(0.0, 0.0)));
+            canvas.set_default_shader();
+        }
+
+        Ok(())
+    }
+}
+
+impl Transition for Luma {
+    fn draw(&mut self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult<bool> {
+        if self.ended {
+            return Ok(false);
+        }
+
+        self.dim.progress = (self.dim.progress + STEP).min(1.0);
+
+        self.draw_frame(ctx, canvas)?;
+
+        if self.dim.progress >= 1.0 {
+            self.ended = true;
+        }
+
+        Ok(!self.ended)
+    }
+
+    fn reversible(&self) -> bool {
+        true
+    }
+
+    fn draw_at(&mut self, ctx: &mut Context, canvas: &mut Canvas, progress: f32) -> GameResult {
+        self.dim.progress = progress;
+        self.draw_frame(ctx, canvas)
+    }
+
+    fn prepare(&mut self, ctx: &mut Context, shaders: &mut Shaders) -> GameResult {
+        self.shader = Some(shaders.get(ctx, "/luma.frag.wgsl")?);
+        self.mask = Some(self.load_mask(ctx)?);
+        Ok(())
+    }
+
+    fn skip_to_end(&mut self, _ctx: &mut Context) -> GameResult {
+        self.ended = true;
+        Ok(())
+    }
+
+    fn update_image(&mut self, _ctx: &mut Context, image: Image) {
+        self.last_image = self.image.replace(image);
+        self.ended = false;
+        self.dim.progress = 0.0;
+    }
+
+    fn set_images(&mut self, _ctx: &mut Context, from: Image, to: Image) {
+        self.last_image = Some(from);
+        self.image = Some(to);
+        self.ended = false;
+        self.dim.progress = 0.0;
+    }
+
+    fn resize(&mut self, _ctx: &mut Context, image: Image) {
+        // the old image is scaled to the size of the new one when it's drawn
+        self.image = Some(image);
+    }
+}
@@ -0,0 +1,124 @@
+use crevice::std140::AsStd140;
+use ggez::graphics::{Canvas, DrawParam, Drawable, Image, Sampler};
+use ggez::*;
+
+use crate::ggez_utils::Point2;
+use crate::transitions::params::TransitionParams;
+use crate::transitions::shaders::Shaders;
+use crate::transitions::transition::{ImagePair, Transition};
+
+/// how much the progress advances every frame
+const STEP: f32 = 0.01;
+
+// Define the input struct for our shader.
+#[derive(AsStd140)]
+struct Dim {
+    progress: f32,
+    softness: f32,
+    invert: u32,
+}
+
+/// A fade where the bright areas of the old image change first, like a luma wipe that uses the
+/// old image as the mask.
+pub struct LumaFade {
+    images: ImagePair,
+    ended: bool,
+    shader: Option<graphics::Shader>,
+    dim: Dim,
+}
+
+impl LumaFade {
+    /// softness is the width of the transition between changed and unchanged areas, in
+    /// luminance, invert changes the dark areas first.
+    pub fn new(softness: f32, invert: bool) -> LumaFade {
+        LumaFade {
+            images: ImagePair::default(),
+            ended: true,
+            shader: None,
+            dim: Dim {
+                progress: 0.0,
+                softness: softness.max(0.0),
+                invert: u32::from(invert),
+            },
+        }
+    }
+
+    /// Parameters:
+    /// - softness: the width of the edge between the images, in luminance (default 0.2)
+    /// - invert: true to change the dark areas first (default false)
+    pub fn from_params(params: &TransitionParams) -> GameResult<LumaFade> {
+        Ok(LumaFade::new(
+            params.get("softness", 0.2)?,
+            params.get("invert", false)?,
+        ))
+    }
+
+    /// Draws the frame at the progress in the parameters of the shader.
+    fn draw_frame(&self, ctx: &mut Context, canvas: &mut Canvas) {
+        if let (Some(image), Some(last_image), Some(shader)) =
+            (self.images.image(), self.images.last_image(), &self.shader)
+        {
+            canvas.set_shader(shader);
+            let shader_params = graphics::ShaderParamsBuilder::new(&self.dim)
+                .images(&[last_image], &[Sampler::linear_clamp()], false)
+                .build(ctx);
+            canvas.set_shader_params(&shader_params);
+
+            image.draw(canvas, DrawParam::new().dest(Point2::new(0.0, 0.0)));
+            canvas.set_default_shader();
+        }
+    }
+}
+
+impl Transition for LumaFade {
+    fn prepare(&mut self, ctx: &mut Context, shaders: &mut Shaders) -> GameResult {
+        self.shader = Some(shaders.get(ctx, "/luma_fade.frag.wgsl")?);
+        Ok(())
+    }
+
+    fn draw(&mut self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult<bool> {
+        if self.ended {
+            return Ok(false);
+        }
+
+        self.dim.progress = (self.dim.progress + STEP).min(1.0);
+
+        self.draw_frame(ctx, canvas);
+
+        if self.dim.progress >= 1.0 {
+            self.ended = true;
+        }
+
+        Ok(!self.ended)
+    }
+
+    fn reversible(&self) -> bool {
+        true
+    }
+
+    fn draw_at(&mut self, ctx: &mut Context, canvas: &mut Canvas, progress: f32) -> GameResult {
+        self.dim.progress = progress;
+        self.draw_frame(ctx, canvas);
+        Ok(())
+    }
+
+    fn skip_to_end(&mut self, _ctx: &mut Context) -> GameResult {
+        self.ended = true;
+        Ok(())
+    }
+
+    fn update_image(&mut self, ctx: &mut Context, image: Image) {
+        let from = self.images.next_from(ctx, &image);
+        self.set_images(ctx, from, image);
+    }
+
+    fn set_images(&mut self, _ctx: &mut Context, from: Image, to: Image) {
+        self.images.set(from, to);
+        self.ended = false;
+        self.dim.progress = 0.0;
+    }
+
+    fn resize(&mut self, _ctx: &mut Context, image: Image) {
+        self.images.resize(image);
+    }
+}
@@ -1,7 +1,25 @@
+pub mod combinators;
+pub mod dip;
 pub mod distortion;
-pub mod quads;
-pub mod transition;
 pub mod fade;
+pub mod gl_transition;
+pub mod kaleidoscope;
+pub mod luma;
+pub mod luma_fade;
+pub mod page_curl;
+pub mod params;
+pub mod particles;
 pub mod pixels;
+pub mod quads;
+pub mod ripple;
+pub mod rotation;
+pub mod shader_transition;
+pub mod shaders;
+pub mod shatter;
 pub mod slides;
-pub mod sphere;
\ No newline at end of file
+pub mod sphere;
+pub mod swirl;
+pub mod transition;
+pub mod wgsl_transition;
+pub mod wipes;
+pub mod zoom;
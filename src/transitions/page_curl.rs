@@ -0,0 +1,255 @@
+use std::str::FromStr;
+
+use crevice::std140::AsStd140;
+use ggez::graphics::{Canvas, DrawParam, Drawable, Image, Sampler};
+use ggez::*;
+
+use crate::ggez_utils::{Point2, Vector2};
+use crate::transitions::params::TransitionParams;
+use crate::transitions::shaders::Shaders;
+use crate::transitions::transition::{ImagePair, Transition};
+
+/// how much the progress advances every frame
+const STEP: f32 = 0.01;
+
+/// The corner the page curls from.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PageCorner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl FromStr for PageCorner {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "top_left" => Ok(PageCorner::TopLeft),
+            "top_right" => Ok(PageCorner::TopRight),
+            "bottom_left" => Ok(PageCorner::BottomLeft),
+            "bottom_right" => Ok(PageCorner::BottomRight),
+            _ => Err("expected one of top_left, top_right, bottom_left, bottom_right".to_string()),
+        }
+    }
+}
+
+/// The direction the curl travels, starting from the corner.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CurlDirection {
+    /// toward the opposite corner
+    Diagonal,
+    /// toward the opposite side, the curl is vertical
+    Horizontal,
+    /// toward the opposite side, the curl is horizontal
+    Vertical,
+}
+
+impl FromStr for CurlDirection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "diagonal" => Ok(CurlDirection::Diagonal),
+            "horizontal" => Ok(CurlDirection::Horizontal),
+            "vertical" => Ok(CurlDirection::Vertical),
+            _ => Err("expected one of diagonal, horizontal, vertical".to_string()),
+        }
+    }
+}
+
+// Define the input struct for our shader.
+#[derive(AsStd140)]
+struct Dim {
+    progress: f32,
+    radius: f32,
+    aspect_ratio: f32,
+    length: f32,
+    corner: mint::Vector2<f32>,
+    direction: mint::Vector2<f32>,
+}
+
+/// The position of the corner, the direction of the curl and the distance it travels to leave
+/// the page, in the space where the height of the image is 1 and the width is the aspect ratio.
+fn curl_geometry(
+    corner: PageCorner,
+    direction: CurlDirection,
+    aspect_ratio: f32,
+) -> (Vector2, Vector2, f32) {
+    let (left, top) = match corner {
+        PageCorner::TopLeft => (true, true),
+        PageCorner::TopRight => (false, true),
+        PageCorner::BottomLeft => (true, false),
+        PageCorner::BottomRight => (false, false),
+    };
+
+    let origin = Vector2::new(
+        if left { 0.0 } else { aspect_ratio },
+        if top { 0.0 } else { 1.0 },
+    );
+
+    let dx = if left { 1.0 } else { -1.0 };
+    let dy = if top { 1.0 } else { -1.0 };
+
+    let direction = match direction {
+        CurlDirection::Diagonal => Vector2::new(dx * aspect_ratio, dy).normalize(),
+        CurlDirection::Horizontal => Vector2::new(dx, 0.0),
+        CurlDirection::Vertical => Vector2::new(0.0, dy),
+    };
+
+    let length = [
+        Vector2::new(0.0, 0.0),
+        Vector2::new(aspect_ratio, 0.0),
+        Vector2::new(0.0, 1.0),
+        Vector2::new(aspect_ratio, 1.0),
+    ]
+    .iter()
+    .map(|it| (it - origin).dot(&direction))
+    .fold(0.0, f32::max);
+
+    (origin, direction, length)
+}
+
+/// A page turn: the old image curls from a corner, showing its back, and reveals the new one.
+pub struct PageCurl {
+    corner: PageCorner,
+    direction: CurlDirection,
+    images: ImagePair,
+    ended: bool,
+    shader: Option<graphics::Shader>,
+    dim: Dim,
+}
+
+impl PageCurl {
+    /// radius is the radius of the curl, relative to the height of the image.
+    pub fn new(corner: PageCorner, direction: CurlDirection, radius: f32) -> PageCurl {
+        PageCurl {
+            corner,
+            direction,
+            images: ImagePair::default(),
+            ended: true,
+            shader: None,
+            dim: Dim {
+                progress: 0.0,
+                radius: radius.max(0.001),
+                aspect_ratio: 1.0,
+                length: 0.0,
+                corner: mint::Vector2 { x: 0.0, y: 0.0 },
+                direction: mint::Vector2 { x: 0.0, y: 0.0 },
+            },
+        }
+    }
+
+    /// Parameters:
+    /// - corner: top_left, top_right, bottom_left, bottom_right (default)
+    /// - direction: diagonal (default), horizontal, vertical
+    /// - radius: the radius of the curl, relative to the height of the image (default 0.1)
+    pub fn from_params(params: &TransitionParams) -> GameResult<PageCurl> {
+        Ok(PageCurl::new(
+            params.get("corner", PageCorner::BottomRight)?,
+            params.get("direction", CurlDirection::Diagonal)?,
+            params.get("radius", 0.1)?,
+        ))
+    }
+
+    fn update_geometry(&mut self, image: &Image) {
+        self.dim.aspect_ratio = image.width() as f32 / image.height() as f32;
+
+        let (corner, direction, length) =
+            curl_geometry(self.corner, self.direction, self.dim.aspect_ratio);
+
+        self.dim.corner = mint::Vector2 {
+            x: corner.x,
+            y: corner.y,
+        };
+        self.dim.direction = mint::Vector2 {
+            x: direction.x,
+            y: direction.y,
+        };
+        self.dim.length = length;
+    }
+}
+
+impl Transition for PageCurl {
+    fn prepare(&mut self, ctx: &mut Context, shaders: &mut Shaders) -> GameResult {
+        self.shader = Some(shaders.get(ctx, "/page_curl.frag.wgsl")?);
+        Ok(())
+    }
+
+    fn draw(&mut self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult<bool> {
+        if self.ended {
+            return Ok(false);
+        }
+
+        self.dim.progress = (self.dim.progress + STEP).min(1.0);
+
+        if let (Some(image), Some(last_image), Some(shader)) =
+            (self.images.image(), self.images.last_image(), &self.shader)
+        {
+            canvas.set_shader(shader);
+            let shader_params = graphics::ShaderParamsBuilder::new(&self.dim)
+                .images(&[last_image], &[Sampler::linear_clamp()], false)
+                .build(ctx);
+            canvas.set_shader_params(&shader_params);
+
+            image.draw(canvas, DrawParam::new().dest(Point2::new(0.0, 0.0)));
+            canvas.set_default_shader();
+        }
+
+        if self.dim.progress >= 1.0 {
+            self.ended = true;
+        }
+
+        Ok(!self.ended)
+    }
+
+    fn skip_to_end(&mut self, _ctx: &mut Context) -> GameResult {
+        self.ended = true;
+        Ok(())
+    }
+
+    fn update_image(&mut self, ctx: &mut Context, image: Image) {
+        let from = self.images.next_from(ctx, &image);
+        self.set_images(ctx, from, image);
+    }
+
+    fn set_images(&mut self, _ctx: &mut Context, from: Image, to: Image) {
+        self.update_geometry(&to);
+        self.images.set(from, to);
+        self.ended = false;
+        self.dim.progress = 0.0;
+    }
+
+    fn resize(&mut self, _ctx: &mut Context, image: Image) {
+        self.update_geometry(&image);
+        self.images.resize(image);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diagonal_geometry() {
+        let (corner, direction, length) =
+            curl_geometry(PageCorner::BottomRight, CurlDirection::Diagonal, 2.0);
+
+        assert_eq!(Vector2::new(2.0, 1.0), corner);
+        assert!((direction.norm() - 1.0).abs() < 1e-6);
+        assert!(direction.x < 0.0 && direction.y < 0.0);
+        // the farthest point is the opposite corner
+        assert!((length - 5.0_f32.sqrt()).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_horizontal_geometry() {
+        let (corner, direction, length) =
+            curl_geometry(PageCorner::TopLeft, CurlDirection::Horizontal, 1.5);
+
+        assert_eq!(Vector2::new(0.0, 0.0), corner);
+        assert_eq!(Vector2::new(1.0, 0.0), direction);
+        assert_eq!(1.5, length);
+    }
+}
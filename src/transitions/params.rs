@@ -0,0 +1,255 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+use std::str::FromStr;
+
+use ggez::{GameError, GameResult};
+
+/// The name of a transition and its parameters, parsed from a spec like
+/// `quads:rows=8,order=spiral`. A value between parentheses can have commas, like the spec of a
+/// child transition in `blend:first=(distortion:amplitude=0.1,frequency=3),second=fade`.
+pub struct TransitionParams {
+    name: String,
+    values: HashMap<String, String>,
+    used: RefCell<HashSet<String>>,
+}
+
+impl TransitionParams {
+    pub fn parse(spec: &str) -> GameResult<TransitionParams> {
+        let (name, values_spec) = match spec.split_once(':') {
+            Some((name, values)) => (name, Some(values)),
+            None => (spec, None),
+        };
+
+        let name = name.trim();
+
+        if name.is_empty() {
+            return Err(GameError::CustomError(format!(
+                "Missing transition name in '{}'",
+                spec
+            )));
+        }
+
+        let mut values = HashMap::new();
+
+        if let Some(values_spec) = values_spec {
+            for value_spec in split_nested(values_spec, ',')?
+                .into_iter()
+                .filter(|it| !it.trim().is_empty())
+            {
+                match value_spec.split_once('=') {
+                    Some((key, value)) => {
+                        values.insert(key.trim().to_string(), unwrap_parens(value).to_string());
+                    }
+                    None => {
+                        return Err(GameError::CustomError(format!(
+                            "Invalid parameter '{}' for transition {}, expected key=value",
+                            value_spec, name
+                        )));
+                    }
+                }
+            }
+        }
+
+        Ok(TransitionParams {
+            name: name.to_string(),
+            values,
+            used: RefCell::new(HashSet::new()),
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the value of the parameter, or None if it's not set.
+    pub fn get_opt<T>(&self, key: &str) -> GameResult<Option<T>>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        self.used.borrow_mut().insert(key.to_string());
+
+        match self.values.get(key) {
+            Some(value) => value.parse().map(Some).map_err(|e| {
+                GameError::CustomError(format!(
+                    "Invalid value '{}' for parameter {} of transition {}: {}",
+                    value, key, self.name, e
+                ))
+            }),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the value of the parameter, or the default if it's not set.
+    pub fn get<T>(&self, key: &str, default: T) -> GameResult<T>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        Ok(self.get_opt(key)?.unwrap_or(default))
+    }
+
+    /// Fails if some of the parameters have not been read by the transition, to catch typos.
+    pub fn check_unused(&self) -> GameResult<()> {
+        let used = self.used.borrow();
+
+        let mut unused = self
+            .values
+            .keys()
+            .filter(|key| !used.contains(*key))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        if unused.is_empty() {
+            Ok(())
+        } else {
+            unused.sort();
+            Err(GameError::CustomError(format!(
+                "Unknown parameters for transition {}: {}",
+                self.name,
+                unused.join(", ")
+            )))
+        }
+    }
+}
+
+/// Splits the spec at the separators that are not between parentheses.
+pub fn split_nested(spec: &str, separator: char) -> GameResult<Vec<&str>> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+
+    for (index, c) in spec.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth = depth.checked_sub(1).ok_or_else(|| {
+                    GameError::CustomError(format!("Unbalanced ')' in '{}'", spec))
+                })?
+            }
+            _ if c == separator && depth == 0 => {
+                parts.push(&spec[start..index]);
+                start = index + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+
+    if depth > 0 {
+        return Err(GameError::CustomError(format!(
+            "Unbalanced '(' in '{}'",
+            spec
+        )));
+    }
+
+    parts.push(&spec[start..]);
+    Ok(parts)
+}
+
+/// The spec without the parentheses around it, if the first one closes at its end.
+pub fn unwrap_parens(spec: &str) -> &str {
+    let spec = spec.trim();
+
+    let inner = match spec.strip_prefix('(').and_then(|it| it.strip_suffix(')')) {
+        Some(inner) => inner,
+        None => return spec,
+    };
+
+    // like (a)+(b), where the first one closes before the end
+    let mut depth = 0usize;
+    for c in inner.chars() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return spec,
+            ')' => depth -= 1,
+            _ => {}
+        }
+    }
+
+    inner.trim()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_name_only() {
+        let params = TransitionParams::parse("fade").unwrap();
+
+        assert_eq!("fade", params.name());
+        assert_eq!(5, params.get("rows", 5).unwrap());
+        assert!(params.check_unused().is_ok());
+    }
+
+    #[test]
+    fn test_parse_values() {
+        let params = TransitionParams::parse("quads:rows=8, order=spiral").unwrap();
+
+        assert_eq!("quads", params.name());
+        assert_eq!(8, params.get("rows", 10).unwrap());
+        assert_eq!(Some("spiral".to_string()), params.get_opt("order").unwrap());
+        assert_eq!(None::<u32>, params.get_opt("columns").unwrap());
+        assert!(params.check_unused().is_ok());
+    }
+
+    #[test]
+    fn test_invalid_value() {
+        let params = TransitionParams::parse("quads:rows=many").unwrap();
+
+        assert!(params.get("rows", 10u32).is_err());
+    }
+
+    #[test]
+    fn test_missing_value() {
+        assert!(TransitionParams::parse("quads:rows").is_err());
+    }
+
+    #[test]
+    fn test_parse_nested() {
+        let params = TransitionParams::parse(
+            "blend:first=(distortion:amplitude=0.1,frequency=3), second=fade,weight=0.3",
+        )
+        .unwrap();
+
+        assert_eq!(
+            Some("distortion:amplitude=0.1,frequency=3".to_string()),
+            params.get_opt("first").unwrap()
+        );
+        assert_eq!(Some("fade".to_string()), params.get_opt("second").unwrap());
+        assert_eq!(0.3, params.get("weight", 0.5).unwrap());
+    }
+
+    #[test]
+    fn test_parse_unbalanced() {
+        assert!(TransitionParams::parse("blend:first=(distortion:amplitude=0.1").is_err());
+        assert!(TransitionParams::parse("blend:first=distortion),second=fade").is_err());
+    }
+
+    #[test]
+    fn test_split_nested() {
+        assert_eq!(
+            vec!["a", "(b+c)", "d"],
+            split_nested("a+(b+c)+d", '+').unwrap()
+        );
+        assert_eq!(vec!["(a,(b,c))"], split_nested("(a,(b,c))", ',').unwrap());
+    }
+
+    #[test]
+    fn test_unwrap_parens() {
+        assert_eq!("a,b", unwrap_parens(" (a,b) "));
+        assert_eq!("a,(b)", unwrap_parens("(a,(b))"));
+        assert_eq!("(a)+(b)", unwrap_parens("(a)+(b)"));
+        assert_eq!("a", unwrap_parens("a"));
+    }
+
+    #[test]
+    fn test_unused() {
+        let params = TransitionParams::parse("quads:rows=8,colums=3").unwrap();
+
+        params.get("rows", 10u32).unwrap();
+
+        assert!(params.check_unused().is_err());
+    }
+}
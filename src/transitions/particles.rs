@@ -0,0 +1,281 @@
+use ggez::graphics::{Canvas, Color, DrawParam, Drawable, Image, InstanceArray, Rect};
+use ggez::*;
+use rand::Rng;
+
+use crate::ggez_utils::{Point2, Vector2};
+use crate::random::{random_seed, shared_rng, SharedRng};
+use crate::transitions::params::TransitionParams;
+use crate::transitions::transition::{ImagePair, Transition};
+
+/// the frames the disintegration takes to sweep the image, from left to right
+const SWEEP_FRAMES: u32 = 90;
+/// the maximum random delay of a particle, after the sweep passed it
+const MAX_DELAY: u32 = 15;
+/// the frames a particle takes to fade out
+const LIFE: u32 = 60;
+
+/// A square of the old image, its color is the texel at the center of the square.
+struct Particle {
+    /// the position in the image
+    origin: Point2,
+    position: Point2,
+    velocity: Vector2,
+    /// the frame the particle starts to move
+    start: u32,
+}
+
+impl Particle {
+    /// Moves the particle at the given frame, the wind pushes it to the right and the
+    /// turbulence makes it swirl.
+    fn update(&mut self, frame: u32, wind: f32, turbulence: f32) {
+        if frame < self.start {
+            return;
+        }
+
+        let t = frame as f32 * 0.1;
+        let swirl = Vector2::new(
+            (self.position.y * 0.02 + t).sin(),
+            (self.position.x * 0.02 + t).cos(),
+        );
+
+        self.velocity += Vector2::new(wind * 0.1, -0.05) + swirl * turbulence * 0.3;
+        self.position += self.velocity;
+    }
+
+    /// From 1, when the particle starts to move, to 0.
+    fn alpha(&self, frame: u32) -> f32 {
+        if frame < self.start {
+            return 1.0;
+        }
+
+        1.0 - ((frame - self.start) as f32 / LIFE as f32).min(1.0)
+    }
+}
+
+/// The columns, the rows and the spacing of a grid of about count squares covering the image.
+fn grid(width: f32, height: f32, count: usize) -> (u32, u32, f32) {
+    let spacing = (width * height / count.max(1) as f32).sqrt().max(1.0);
+
+    (
+        (width / spacing).ceil() as u32,
+        (height / spacing).ceil() as u32,
+        spacing,
+    )
+}
+
+/// The old image disintegrates in colored particles that drift away with the wind, from left to
+/// right. The particles are drawn in a single instanced draw, each one takes its color from the
+/// texel of the old image at its center.
+pub struct Particles {
+    count: usize,
+    size: Option<f32>,
+    wind: f32,
+    turbulence: f32,
+    particles: Vec<Particle>,
+    images: ImagePair,
+    instances: Option<InstanceArray>,
+    frame: u32,
+    spacing: f32,
+    rng: SharedRng,
+}
+
+impl Particles {
+    /// size is the side of the particles in pixels, by default it's the spacing between them.
+    pub fn new(count: usize, size: Option<f32>, wind: f32, turbulence: f32) -> Particles {
+        Particles {
+            count: count.max(1),
+            size,
+            wind,
+            turbulence,
+            particles: Vec::new(),
+            images: ImagePair::default(),
+            instances: None,
+            frame: 0,
+            spacing: 1.0,
+            rng: shared_rng(random_seed()),
+        }
+    }
+
+    /// Parameters:
+    /// - count: the number of particles (default 6000)
+    /// - size: the side of the particles in pixels (default the space between them)
+    /// - wind: how fast the particles are blown to the right (default 1)
+    /// - turbulence: how much the particles swirl (default 1)
+    pub fn from_params(params: &TransitionParams) -> GameResult<Particles> {
+        Ok(Particles::new(
+            params.get("count", 6000)?,
+            params.get_opt("size")?,
+            params.get("wind", 1.0)?,
+            params.get("turbulence", 1.0)?,
+        ))
+    }
+
+    fn create_particles(&mut self, width: f32, height: f32) {
+        let (columns, rows, spacing) = grid(width, height, self.count);
+        let mut rng = self.rng.borrow_mut();
+
+        self.spacing = spacing;
+        self.particles.clear();
+
+        for row in 0..rows {
+            for column in 0..columns {
+                let origin = Point2::new(column as f32 * spacing, row as f32 * spacing);
+
+                self.particles.push(Particle {
+                    origin,
+                    position: origin,
+                    velocity: Vector2::new(rng.gen_range(-0.5, 0.5), rng.gen_range(-0.5, 0.5)),
+                    start: (origin.x / width * SWEEP_FRAMES as f32) as u32
+                        + rng.gen_range(0, MAX_DELAY),
+                });
+            }
+        }
+    }
+
+    fn ended(&self) -> bool {
+        self.frame >= SWEEP_FRAMES + MAX_DELAY + LIFE
+    }
+}
+
+impl Transition for Particles {
+    fn set_rng(&mut self, rng: SharedRng) {
+        self.rng = rng;
+    }
+
+    fn draw(&mut self, _ctx: &mut Context, canvas: &mut Canvas) -> GameResult<bool> {
+        if self.ended() {
+            return Ok(false);
+        }
+
+        self.frame += 1;
+
+        if let (Some(image), Some(last_image), Some(instances)) = (
+            self.images.image(),
+            self.images.last_image(),
+            &mut self.instances,
+        ) {
+            let width = image.width() as f32;
+            let height = image.height() as f32;
+
+            image.draw(canvas, DrawParam::new().dest(Point2::new(0.0, 0.0)));
+
+            // the part of the old image the sweep has not reached yet
+            let front = (self.frame as f32 / SWEEP_FRAMES as f32).min(1.0);
+            if front < 1.0 {
+                last_image.draw(
+                    canvas,
+                    DrawParam::new()
+                        .src(Rect::new(front, 0.0, 1.0 - front, 1.0))
+                        .dest(Point2::new(front * width, 0.0))
+                        .scale([
+                            width / last_image.width() as f32,
+                            height / last_image.height() as f32,
+                        ]),
+                );
+            }
+
+            let size = self.size.unwrap_or(self.spacing);
+            let texel_width = 1.0 / last_image.width() as f32;
+            let texel_height = 1.0 / last_image.height() as f32;
+
+            instances.clear();
+
+            for particle in self.particles.iter_mut() {
+                if particle.origin.x >= front * width {
+                    continue;
+                }
+
+                particle.update(self.frame, self.wind, self.turbulence);
+
+                let alpha = particle.alpha(self.frame);
+                if alpha <= 0.0 {
+                    continue;
+                }
+
+                // a single texel, scaled to the size of the particle
+                let u = (particle.origin.x + self.spacing / 2.0) / width;
+                let v = (particle.origin.y + self.spacing / 2.0) / height;
+
+                instances.push(
+                    DrawParam::new()
+                        .src(Rect::new(u, v, texel_width, texel_height))
+                        .dest(particle.position)
+                        .scale([size, size])
+                        .color(Color::new(1.0, 1.0, 1.0, alpha)),
+                );
+            }
+
+            canvas.draw(&*instances, DrawParam::default());
+        }
+
+        Ok(!self.ended())
+    }
+
+    fn skip_to_end(&mut self, _ctx: &mut Context) -> GameResult {
+        self.frame = SWEEP_FRAMES + MAX_DELAY + LIFE;
+        Ok(())
+    }
+
+    fn update_image(&mut self, ctx: &mut Context, image: Image) {
+        let from = self.images.next_from(ctx, &image);
+        self.set_images(ctx, from, image);
+    }
+
+    fn set_images(&mut self, ctx: &mut Context, from: Image, to: Image) {
+        self.create_particles(to.width() as f32, to.height() as f32);
+        self.instances = Some(InstanceArray::new(ctx, from.clone()));
+        self.images.set(from, to);
+        self.frame = 0;
+    }
+
+    fn resize(&mut self, _ctx: &mut Context, image: Image) {
+        // the particles do not fit anymore, the new image is drawn once and the transition ends
+        self.particles.clear();
+        self.images.resize(image);
+        self.frame = SWEEP_FRAMES + MAX_DELAY + LIFE - 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grid() {
+        let (columns, rows, spacing) = grid(400.0, 200.0, 800);
+
+        assert_eq!(10.0, spacing);
+        assert_eq!(40, columns);
+        assert_eq!(20, rows);
+    }
+
+    #[test]
+    fn test_particles_start_from_the_left() {
+        let mut particles = Particles::new(100, None, 1.0, 1.0);
+        particles.create_particles(100.0, 100.0);
+
+        let first = particles.particles.first().unwrap();
+        let last = particles.particles.last().unwrap();
+
+        assert!(first.start < MAX_DELAY);
+        assert!(last.start >= SWEEP_FRAMES * 9 / 10);
+    }
+
+    #[test]
+    fn test_particles_fade_out() {
+        let mut particle = Particle {
+            origin: Point2::new(10.0, 10.0),
+            position: Point2::new(10.0, 10.0),
+            velocity: Vector2::new(0.0, 0.0),
+            start: 5,
+        };
+
+        particle.update(4, 1.0, 0.0);
+        assert_eq!(Point2::new(10.0, 10.0), particle.position);
+        assert_eq!(1.0, particle.alpha(4));
+
+        particle.update(5, 1.0, 0.0);
+        assert!(particle.position.x > 10.0);
+        assert_eq!(0.0, particle.alpha(5 + LIFE));
+    }
+}
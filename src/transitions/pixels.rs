@@ -1,99 +1,348 @@
-use ggez::graphics::{
-    Canvas, Color, DrawMode, DrawParam, Drawable, Image, Mesh, MeshBuilder, Rect,
-};
+use std::str::FromStr;
+
+use crevice::std140::AsStd140;
+use ggez::graphics::{Canvas, DrawParam, Drawable, Image, ImageFormat};
 use ggez::*;
-use image::RgbaImage;
 use rand::Rng;
 
 use crate::ggez_utils::Point2;
+use crate::random::{random_seed, shared_rng, SharedRng};
+use crate::transitions::params::TransitionParams;
+use crate::transitions::shaders::Shaders;
 use crate::transitions::transition::*;
 
+/// how much the progress advances every frame
+const STEP: f32 = 1.0 / 300.0;
+/// the resolution of the histogram used to equalize the thresholds
+const LEVELS: usize = 65536;
+
+/// The order in which the blocks of pixels are revealed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PixelsPattern {
+    Random,
+    /// ordered dithering with an 8x8 Bayer matrix
+    Bayer,
+    /// along a Hilbert curve
+    Hilbert,
+    /// from the center to the border
+    Radial,
+}
+
+impl FromStr for PixelsPattern {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "random" => Ok(PixelsPattern::Random),
+            "bayer" => Ok(PixelsPattern::Bayer),
+            "hilbert" => Ok(PixelsPattern::Hilbert),
+            "radial" => Ok(PixelsPattern::Radial),
+            _ => Err("expected one of random, bayer, hilbert, radial".to_string()),
+        }
+    }
+}
+
+// Define the input struct for our shader.
+#[derive(AsStd140)]
+struct Dim {
+    progress: f32,
+    block_size: f32,
+    width: f32,
+    height: f32,
+}
+
+/// A dissolve where each block of pixels is revealed when the progress reaches its threshold.
+/// The thresholds are computed on the CPU once per image and stored in a texture, with one texel
+/// per block, so each frame is a single draw.
 pub struct Pixels {
-    pixels: Vec<Pixel>,
-    image: Option<RgbaImage>,
+    block_size: u32,
+    pattern: PixelsPattern,
+    image: Option<Image>,
+    thresholds: Option<Image>,
+    shader: Option<graphics::Shader>,
+    dim: Dim,
+    rng: SharedRng,
 }
 
 impl Pixels {
-    pub fn new() -> Pixels {
+    pub fn new(block_size: u32, pattern: PixelsPattern) -> Pixels {
         Pixels {
-            pixels: Vec::new(),
+            block_size: block_size.max(1),
+            pattern,
             image: None,
+            thresholds: None,
+            shader: None,
+            dim: Dim {
+                progress: 1.0,
+                block_size: block_size.max(1) as f32,
+                width: 1.0,
+                height: 1.0,
+            },
+            rng: shared_rng(random_seed()),
         }
     }
-}
 
-struct Pixel {
-    x: u16,
-    y: u16,
-}
+    /// Parameters:
+    /// - block: the side of the blocks in pixels (default 1)
+    /// - pattern: random (default), bayer, hilbert, radial
+    pub fn from_params(params: &TransitionParams) -> GameResult<Pixels> {
+        let block_size = params.get("block", 1)?;
+        let pattern = params.get("pattern", PixelsPattern::Random)?;
 
-impl Pixel {
-    pub fn new(x: u16, y: u16) -> Pixel {
-        Pixel { x, y }
+        Ok(Pixels::new(block_size, pattern))
     }
 }
 
 impl Transition for Pixels {
+    fn set_rng(&mut self, rng: SharedRng) {
+        self.rng = rng;
+    }
+
     fn draw(&mut self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult<bool> {
-        if self.pixels.is_empty() {
+        if self.dim.progress > 1.0 {
             return Ok(false);
         }
 
-        let mut mesh_builder = MeshBuilder::new();
+        self.dim.progress += STEP;
+
+        if let (Some(image), Some(thresholds), Some(shader)) =
+            (&self.image, &self.thresholds, &self.shader)
+        {
+            canvas.set_shader(shader);
+
+            let shader_params = graphics::ShaderParamsBuilder::new(&self.dim)
+                .images(&[thresholds], &[], false)
+                .build(ctx);
+            canvas.set_shader_params(&shader_params);
+
+            image.draw(canvas, DrawParam::new().dest(Point2::new(0.0, 0.0)));
+        }
+
+        Ok(true)
+    }
+
+    fn prepare(&mut self, ctx: &mut Context, shaders: &mut Shaders) -> GameResult {
+        self.shader = Some(shaders.get(ctx, "/pixels.frag.wgsl")?);
+        Ok(())
+    }
+
+    fn skip_to_end(&mut self, _ctx: &mut Context) -> GameResult {
+        // past the end, the next draw returns false
+        self.dim.progress = 2.0;
+        Ok(())
+    }
 
-        match self.image {
-            Some(ref image) => {
-                let count = image.width() * image.height() / 300;
+    fn update_image(&mut self, ctx: &mut Context, image: Image) {
+        let columns = (image.width() + self.block_size - 1) / self.block_size;
+        let rows = (image.height() + self.block_size - 1) / self.block_size;
 
-                for _i in 0..count {
-                    if let Some(pixel) = self.pixels.pop() {
-                        let pixel_rgba = image.get_pixel(pixel.x as u32, pixel.y as u32);
-                        let pixel_color = pixel_to_color(pixel_rgba);
+        let mut rng = self.rng.borrow_mut();
+        let keys = pattern_keys(self.pattern, columns, rows, &mut *rng);
+        let thresholds = equalize(&keys);
+
+        // the thresholds are stored in 16 bits, the most significant in the red channel
+        let mut pixels = Vec::with_capacity(thresholds.len() * 4);
+        for threshold in thresholds {
+            pixels.extend_from_slice(&[(threshold >> 8) as u8, threshold as u8, 0, 255]);
+        }
 
-                        let rect = Rect::new(pixel.x as f32, pixel.y as f32, 1.0, 1.0);
+        self.thresholds = Some(Image::from_pixels(
+            ctx,
+            &pixels,
+            ImageFormat::Rgba8Unorm,
+            columns,
+            rows,
+        ));
 
-                        mesh_builder.rectangle(DrawMode::fill(), rect, pixel_color)?;
-                    } else {
-                        break;
-                    }
+        self.dim.progress = 0.0;
+        self.dim.width = image.width() as f32;
+        self.dim.height = image.height() as f32;
+        self.image = Some(image);
+    }
+}
+
+/// Returns a key for each block, row by row: blocks with a lower key are revealed first.
+fn pattern_keys<R: Rng>(pattern: PixelsPattern, columns: u32, rows: u32, rng: &mut R) -> Vec<f32> {
+    let mut keys = Vec::with_capacity((columns * rows) as usize);
+
+    match pattern {
+        PixelsPattern::Random => {
+            for _ in 0..columns * rows {
+                keys.push(rng.gen::<f32>());
+            }
+        }
+        PixelsPattern::Bayer => {
+            for y in 0..rows {
+                for x in 0..columns {
+                    keys.push(bayer(x % 8, y % 8) as f32);
+                }
+            }
+        }
+        PixelsPattern::Hilbert => {
+            let side = columns.max(rows).next_power_of_two();
+            for y in 0..rows {
+                for x in 0..columns {
+                    keys.push(hilbert(side, x, y) as f32);
                 }
             }
-            None => {}
         }
+        PixelsPattern::Radial => {
+            let center_x = columns as f32 / 2.0;
+            let center_y = rows as f32 / 2.0;
+            for y in 0..rows {
+                for x in 0..columns {
+                    let dx = x as f32 + 0.5 - center_x;
+                    let dy = y as f32 + 0.5 - center_y;
+                    // a bit of noise breaks the rings of blocks with the same distance
+                    keys.push((dx * dx + dy * dy).sqrt() + rng.gen::<f32>() * 0.5);
+                }
+            }
+        }
+    }
+
+    keys
+}
 
-        let mesh = Mesh::from_data(ctx, mesh_builder.build());
+/// The value of the cell of an 8x8 Bayer matrix, between 0 and 63.
+fn bayer(x: u32, y: u32) -> u32 {
+    let mut result = 0;
+    for bit in 0..3 {
+        let bx = (x >> bit) & 1;
+        let by = (y >> bit) & 1;
+        result |= ((bx ^ by) << (5 - 2 * bit)) | (by << (4 - 2 * bit));
+    }
+    result
+}
 
-        let param = DrawParam::new().dest(Point2::new(0.0, 0.0));
+/// The distance along a Hilbert curve filling a square of the given side (a power of two).
+fn hilbert(side: u32, x: u32, y: u32) -> u64 {
+    let (mut x, mut y) = (x, y);
+    let mut d: u64 = 0;
+    let mut s = side / 2;
 
-        mesh.draw(canvas, param);
+    while s > 0 {
+        let rx = u32::from(x & s > 0);
+        let ry = u32::from(y & s > 0);
+        d += s as u64 * s as u64 * ((3 * rx) ^ ry) as u64;
 
-        Ok(true)
+        // rotate the quadrant
+        if ry == 0 {
+            if rx == 1 {
+                x = side - 1 - x;
+                y = side - 1 - y;
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+
+        s /= 2;
     }
 
-    fn update_image(&mut self, ctx: &mut Context, image: Image) {
-        self.pixels.clear();
+    d
+}
+
+/// Maps the keys to 16 bit thresholds with (almost) uniform distribution, keeping their order,
+/// so the same number of blocks is revealed at each step of the progress.
+fn equalize(keys: &[f32]) -> Vec<u16> {
+    let min = keys.iter().cloned().fold(f32::INFINITY, f32::min);
+    let max = keys.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+    let range = if max > min { max - min } else { 1.0 };
+
+    let level = |key: f32| (((key - min) / range) * (LEVELS - 1) as f32) as usize;
+
+    let mut histogram = vec![0u32; LEVELS];
+    for key in keys {
+        histogram[level(*key)] += 1;
+    }
+
+    // the number of keys below each level
+    let mut below = vec![0u32; LEVELS];
+    let mut count = 0;
+    for (i, n) in histogram.iter().enumerate() {
+        below[i] = count;
+        count += n;
+    }
+
+    let total = keys.len().max(1) as f32;
+
+    keys.iter()
+        .map(|key| {
+            let level = level(*key);
+            // the keys in the same level are spread evenly on the range of the level
+            let middle = below[level] as f32 + histogram[level] as f32 / 2.0;
+            (middle / total * 65535.0) as u16
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+
+    #[test]
+    fn test_bayer() {
+        assert_eq!(0, bayer(0, 0));
+        assert_eq!(32, bayer(1, 0));
+        assert_eq!(48, bayer(0, 1));
+        assert_eq!(16, bayer(1, 1));
+
+        let mut values = (0..64).map(|i| bayer(i % 8, i / 8)).collect::<Vec<_>>();
+        values.sort();
+        assert_eq!((0..64).collect::<Vec<_>>(), values);
+    }
+
+    #[test]
+    fn test_hilbert() {
+        assert_eq!(0, hilbert(2, 0, 0));
+        assert_eq!(1, hilbert(2, 0, 1));
+        assert_eq!(2, hilbert(2, 1, 1));
+        assert_eq!(3, hilbert(2, 1, 0));
 
-        for x in 0..image.width() {
-            for y in 0..image.height() {
-                self.pixels.push(Pixel::new(x as u16, y as u16));
+        let mut values = (0..64)
+            .map(|i| hilbert(8, i % 8, i / 8))
+            .collect::<Vec<_>>();
+        values.sort();
+        assert_eq!((0..64).collect::<Vec<_>>(), values);
+    }
+
+    #[test]
+    fn test_hilbert_is_continuous() {
+        let side = 16;
+        let mut points = vec![(0, 0); (side * side) as usize];
+        for y in 0..side {
+            for x in 0..side {
+                points[hilbert(side, x, y) as usize] = (x as i32, y as i32);
             }
         }
 
-        let mut rng = rand::thread_rng();
+        for pair in points.windows(2) {
+            let distance = (pair[0].0 - pair[1].0).abs() + (pair[0].1 - pair[1].1).abs();
+            assert_eq!(1, distance);
+        }
+    }
+
+    #[test]
+    fn test_equalize_keeps_order() {
+        let keys = vec![0.0, 100.0, 1.0, 2.0, 3.0];
 
-        rng.shuffle(&mut self.pixels);
+        let thresholds = equalize(&keys);
 
-        let pixels = image.to_pixels(ctx).unwrap();
-        let i = RgbaImage::from_raw(image.width(), image.height(), pixels).unwrap();
-        self.image = Some(i);
+        assert!(thresholds[0] < thresholds[2]);
+        assert!(thresholds[2] < thresholds[3]);
+        assert!(thresholds[3] < thresholds[4]);
+        assert!(thresholds[4] < thresholds[1]);
     }
-}
 
-fn pixel_to_color(pixel: &image::Rgba<u8>) -> Color {
-    Color::new(
-        pixel.0[0] as f32 / 255.0,
-        pixel.0[1] as f32 / 255.0,
-        pixel.0[2] as f32 / 255.0,
-        pixel.0[3] as f32 / 255.0,
-    )
+    #[test]
+    fn test_equalize_is_uniform() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let keys = pattern_keys(PixelsPattern::Radial, 200, 100, &mut rng);
+
+        let thresholds = equalize(&keys);
+
+        let below_half = thresholds.iter().filter(|it| **it < 32768).count();
+        assert!((below_half as i32 - 10_000).abs() < 100);
+    }
 }
@@ -1,63 +1,147 @@
+use std::collections::VecDeque;
+use std::str::FromStr;
+
 use ggez::graphics::{Canvas, DrawParam, Drawable, Image, Rect};
 use ggez::*;
 use rand::Rng;
 
 use crate::ggez_utils::Point2;
+use crate::random::{random_seed, shared_rng, SharedRng};
+use crate::transitions::params::TransitionParams;
 use crate::transitions::transition::*;
 
-const V_QUADS: u16 = 10;
+const DEFAULT_ROWS: u32 = 10;
+/// the number of frames needed to reveal all the quads, regardless of the size of the grid
+const FRAMES: usize = 180;
+
+/// How the image is divided into quads.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum QuadsGrid {
+    /// The given number of rows of square quads, the quads of the last column can be partial.
+    Rows(u32),
+    /// The given number of rows and columns, the quads are not square.
+    RowsAndColumns(u32, u32),
+    /// Square quads with the given side in pixels, the quads of the last row and column can be
+    /// partial.
+    CellSize(u32),
+}
+
+/// The order in which the quads are revealed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum QuadsOrder {
+    Random,
+    /// row by row, from left to right
+    Raster,
+    /// clockwise, from the border to the center
+    Spiral,
+    /// from the top left corner to the bottom right corner
+    Diagonal,
+    /// from the center to the border
+    Center,
+}
+
+impl FromStr for QuadsOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "random" => Ok(QuadsOrder::Random),
+            "raster" => Ok(QuadsOrder::Raster),
+            "spiral" => Ok(QuadsOrder::Spiral),
+            "diagonal" => Ok(QuadsOrder::Diagonal),
+            "center" => Ok(QuadsOrder::Center),
+            _ => Err("expected one of random, raster, spiral, diagonal, center".to_string()),
+        }
+    }
+}
 
 pub struct Quads {
-    quads: Vec<Quad>,
+    grid: QuadsGrid,
+    order: QuadsOrder,
+    quads: VecDeque<Quad>,
+    quads_per_frame: usize,
     image: Option<Image>,
+    rng: SharedRng,
 }
 
 impl Quads {
-    pub fn new() -> Quads {
+    pub fn new(grid: QuadsGrid, order: QuadsOrder) -> Quads {
         Quads {
-            quads: Vec::new(),
+            grid,
+            order,
+            quads: VecDeque::new(),
+            quads_per_frame: 1,
             image: None,
+            rng: shared_rng(random_seed()),
+        }
+    }
+
+    /// Parameters:
+    /// - rows: the number of rows (default 10)
+    /// - columns: the number of columns, by default it's computed to have square quads
+    /// - size: the side of the quads in pixels, alternative to rows and columns
+    /// - order: random (default), raster, spiral, diagonal, center
+    pub fn from_params(params: &TransitionParams) -> GameResult<Quads> {
+        let rows = params.get_opt::<u32>("rows")?;
+        let columns = params.get_opt::<u32>("columns")?;
+        let size = params.get_opt::<u32>("size")?;
+
+        let grid = match (rows, columns, size) {
+            (None, None, Some(size)) => QuadsGrid::CellSize(size),
+            (rows, None, None) => QuadsGrid::Rows(rows.unwrap_or(DEFAULT_ROWS)),
+            (Some(rows), Some(columns), None) => QuadsGrid::RowsAndColumns(rows, columns),
+            _ => {
+                return Err(GameError::CustomError(
+                    "quads accepts rows, rows and columns, or size".to_string(),
+                ))
+            }
+        };
+
+        if rows == Some(0) || columns == Some(0) || size == Some(0) {
+            return Err(GameError::CustomError(
+                "quads rows, columns and size must be greater than zero".to_string(),
+            ));
         }
+
+        let order = params.get("order", QuadsOrder::Random)?;
+
+        Ok(Quads::new(grid, order))
     }
 }
 
-#[derive(Debug)]
+/// A cell of the grid, in pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
 struct Quad {
-    x: u16,
-    y: u16,
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
 }
 
-impl Quad {
-    pub fn new(x: u16, y: u16) -> Quad {
-        Quad { x, y }
+impl Transition for Quads {
+    fn set_rng(&mut self, rng: SharedRng) {
+        self.rng = rng;
     }
-}
 
-impl Transition for Quads {
-    fn draw(&mut self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult<bool> {
+    fn draw(&mut self, _ctx: &mut Context, canvas: &mut Canvas) -> GameResult<bool> {
         if self.quads.is_empty() {
             return Ok(false);
         }
 
-        let mut rng = rand::thread_rng();
-
-        let index = rng.gen_range(0usize, self.quads.len());
-        let removed = self.quads.remove(index);
-        //println!("draw quad {:?}", removed);
+        if let Some(image) = &self.image {
+            for _ in 0..self.quads_per_frame {
+                let quad = match self.quads.pop_front() {
+                    Some(quad) => quad,
+                    None => break,
+                };
 
-        match &self.image {
-            Some(image) => {
-                let quad_size = image.height() as f32 / V_QUADS as f32;
-                let quad_x = removed.x as f32 * quad_size;
-                let quad_y = removed.y as f32 * quad_size;
-
-                let x = quad_x / image.width() as f32;
-                let y = quad_y / image.height() as f32;
-                let width = quad_size / image.width() as f32;
-                let height = quad_size / image.height() as f32;
-
-                let src = Rect::new(x, y, width, height);
-                let dest = Point2::new(quad_x, quad_y);
+                let src = Rect::new(
+                    quad.x / image.width() as f32,
+                    quad.y / image.height() as f32,
+                    quad.width / image.width() as f32,
+                    quad.height / image.height() as f32,
+                );
+                let dest = Point2::new(quad.x, quad.y);
 
                 // println!("quad {src:?} -> {dest}");
 
@@ -65,32 +149,228 @@ impl Transition for Quads {
 
                 image.draw(canvas, draw_param);
             }
-            None => {}
         }
 
-        //        canvas.finish(ctx)?;
-
         Ok(true)
     }
 
-    fn update_image(&mut self, ctx: &mut Context, image: Image) {
+    fn skip_to_end(&mut self, _ctx: &mut Context) -> GameResult {
+        self.quads.clear();
+        Ok(())
+    }
+
+    fn update_image(&mut self, _ctx: &mut Context, image: Image) {
         // println!("update_image {},{}", image.width(), image.height());
 
-        self.quads.clear();
+        let (columns, rows, cells) =
+            grid_cells(self.grid, image.width() as f32, image.height() as f32);
+
+        let mut order = reveal_order(self.order, columns, rows, &cells);
+
+        if self.order == QuadsOrder::Random {
+            let mut rng = self.rng.borrow_mut();
+            rng.shuffle(&mut order);
+        }
+
+        self.quads = order.into_iter().map(|i| cells[i]).collect();
+        self.quads_per_frame = (self.quads.len() + FRAMES - 1) / FRAMES;
+        self.image = Some(image);
+    }
+}
+
+/// Returns the number of columns and rows and the cells of the grid, row by row.
+fn grid_cells(grid: QuadsGrid, width: f32, height: f32) -> (usize, usize, Vec<Quad>) {
+    let (cell_width, cell_height) = match grid {
+        QuadsGrid::Rows(rows) => (height / rows as f32, height / rows as f32),
+        QuadsGrid::RowsAndColumns(rows, columns) => (width / columns as f32, height / rows as f32),
+        QuadsGrid::CellSize(size) => (size as f32, size as f32),
+    };
+
+    // the tolerance, of half a pixel, avoids an almost empty column (or row) due to rounding
+    // errors, but not one that is at least a pixel wide
+    let columns = ((width - 0.5) / cell_width).ceil().max(1.0) as usize;
+    let rows = ((height - 0.5) / cell_height).ceil().max(1.0) as usize;
+
+    let mut cells = Vec::with_capacity(columns * rows);
+
+    for row in 0..rows {
+        for column in 0..columns {
+            let x = column as f32 * cell_width;
+            let y = row as f32 * cell_height;
+            cells.push(Quad {
+                x,
+                y,
+                width: cell_width.min(width - x),
+                height: cell_height.min(height - y),
+            });
+        }
+    }
+
+    (columns, rows, cells)
+}
 
-        let quad_size = image.height() as f32 / V_QUADS as f32;
+/// Returns the indexes of the cells (as returned by grid_cells) in the order they are revealed.
+/// For random order the cells are in raster order and must be shuffled.
+fn reveal_order(order: QuadsOrder, columns: usize, rows: usize, cells: &[Quad]) -> Vec<usize> {
+    let mut indexes = (0..columns * rows).collect::<Vec<_>>();
 
-        let h_quads = (image.width() as f32 / quad_size) as u16;
-        let v_quads = (image.height() as f32 / quad_size) as u16;
+    match order {
+        QuadsOrder::Random | QuadsOrder::Raster => {}
+        QuadsOrder::Spiral => indexes = spiral(columns, rows),
+        QuadsOrder::Diagonal => indexes.sort_by_key(|i| (i % columns + i / columns, i / columns)),
+        QuadsOrder::Center => {
+            let (center_x, center_y) = match (cells.first(), cells.last()) {
+                (Some(first), Some(last)) => (
+                    (first.x + last.x + last.width) / 2.0,
+                    (first.y + last.y + last.height) / 2.0,
+                ),
+                _ => (0.0, 0.0),
+            };
 
-        for x in 0..h_quads {
-            for y in 0..v_quads {
-                let quad = Quad::new(x, y);
-                //println!("quad {:?}", &quad);
-                self.quads.push(quad);
+            let distance = |quad: &Quad| {
+                let dx = quad.x + quad.width / 2.0 - center_x;
+                let dy = quad.y + quad.height / 2.0 - center_y;
+                dx * dx + dy * dy
+            };
+
+            indexes.sort_by(|a, b| distance(&cells[*a]).total_cmp(&distance(&cells[*b])));
+        }
+    }
+
+    indexes
+}
+
+fn spiral(columns: usize, rows: usize) -> Vec<usize> {
+    let mut result = Vec::with_capacity(columns * rows);
+
+    let (mut left, mut top) = (0, 0);
+    let (mut right, mut bottom) = (columns, rows);
+
+    while left < right && top < bottom {
+        for column in left..right {
+            result.push(top * columns + column);
+        }
+        for row in top + 1..bottom {
+            result.push(row * columns + right - 1);
+        }
+        if bottom - top > 1 {
+            for column in (left..right - 1).rev() {
+                result.push((bottom - 1) * columns + column);
+            }
+        }
+        if right - left > 1 {
+            for row in (top + 1..bottom - 1).rev() {
+                result.push(row * columns + left);
             }
         }
+        left += 1;
+        top += 1;
+        right -= 1;
+        bottom -= 1;
+    }
 
-        self.image = Some(image);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_permutation(mut indexes: Vec<usize>, len: usize) {
+        indexes.sort();
+        assert_eq!((0..len).collect::<Vec<_>>(), indexes);
+    }
+
+    #[test]
+    fn test_rows_grid_with_partial_column() {
+        let (columns, rows, cells) = grid_cells(QuadsGrid::Rows(10), 1920.0, 1080.0);
+
+        assert_eq!(18, columns);
+        assert_eq!(10, rows);
+
+        let last = cells.last().unwrap();
+        assert_eq!(1080.0 - 108.0, last.y);
+        assert_eq!(1920.0 - 17.0 * 108.0, last.width);
+        assert_eq!(108.0, last.height);
+    }
+
+    #[test]
+    fn test_cell_size_grid() {
+        let (columns, rows, cells) = grid_cells(QuadsGrid::CellSize(100), 250.0, 120.0);
+
+        assert_eq!(3, columns);
+        assert_eq!(2, rows);
+
+        let last = cells.last().unwrap();
+        assert_eq!(
+            Quad {
+                x: 200.0,
+                y: 100.0,
+                width: 50.0,
+                height: 20.0
+            },
+            *last
+        );
+    }
+
+    #[test]
+    fn test_cell_size_grid_with_one_pixel_left() {
+        let (columns, rows, cells) = grid_cells(QuadsGrid::CellSize(1920), 1921.0, 1080.0);
+
+        assert_eq!(2, columns);
+        assert_eq!(1, rows);
+        assert_eq!(1.0, cells.last().unwrap().width);
+    }
+
+    #[test]
+    fn test_rows_and_columns_grid() {
+        let (columns, rows, cells) = grid_cells(QuadsGrid::RowsAndColumns(3, 7), 1000.0, 300.0);
+
+        assert_eq!(7, columns);
+        assert_eq!(3, rows);
+        assert_eq!(21, cells.len());
+        assert_eq!(100.0, cells[0].height);
+    }
+
+    #[test]
+    fn test_spiral() {
+        assert_eq!(vec![0, 1, 2, 5, 8, 7, 6, 3, 4], spiral(3, 3));
+        assert_eq!(vec![0, 1, 2, 3, 7, 6, 5, 4], spiral(4, 2));
+        assert_eq!(vec![0, 1, 2], spiral(1, 3));
+    }
+
+    #[test]
+    fn test_orders_reveal_all_quads() {
+        let (columns, rows, cells) = grid_cells(QuadsGrid::CellSize(30), 250.0, 120.0);
+
+        for order in [
+            QuadsOrder::Random,
+            QuadsOrder::Raster,
+            QuadsOrder::Spiral,
+            QuadsOrder::Diagonal,
+            QuadsOrder::Center,
+        ] {
+            assert_permutation(reveal_order(order, columns, rows, &cells), cells.len());
+        }
+    }
+
+    #[test]
+    fn test_diagonal() {
+        let (columns, rows, cells) = grid_cells(QuadsGrid::RowsAndColumns(2, 3), 3.0, 2.0);
+
+        assert_eq!(
+            vec![0, 1, 3, 2, 4, 5],
+            reveal_order(QuadsOrder::Diagonal, columns, rows, &cells)
+        );
+    }
+
+    #[test]
+    fn test_center_starts_from_the_middle() {
+        let (columns, rows, cells) = grid_cells(QuadsGrid::RowsAndColumns(3, 3), 3.0, 3.0);
+
+        assert_eq!(
+            4,
+            reveal_order(QuadsOrder::Center, columns, rows, &cells)[0]
+        );
     }
 }
@@ -0,0 +1,71 @@
+use crevice::std140::AsStd140;
+use ggez::*;
+
+use crate::transitions::params::TransitionParams;
+use crate::transitions::shader_transition::{ShaderDim, ShaderTransition};
+use crate::velocity::*;
+
+// Define the input struct for our shader, public for the type of the transition, but it can be
+// built only here.
+#[derive(AsStd140)]
+pub struct RippleDim {
+    progress: f32,
+    amplitude: f32,
+    frequency: f32,
+    aspect_ratio: f32,
+    center: mint::Vector2<f32>,
+}
+
+impl ShaderDim for RippleDim {
+    fn set_progress(&mut self, progress: f32) {
+        self.progress = progress;
+    }
+
+    fn set_aspect_ratio(&mut self, aspect_ratio: f32) {
+        self.aspect_ratio = aspect_ratio;
+    }
+}
+
+/// A drop falls on the old image, and the new one spreads from there with the waves.
+pub type Ripple = ShaderTransition<RippleDim>;
+
+impl Ripple {
+    /// amplitude is relative to the height of the image, frequency is the number of waves per
+    /// height of the image, the center is relative to the size of the image.
+    pub fn new(amplitude: f32, frequency: f32, center_x: f32, center_y: f32) -> Ripple {
+        let dim = RippleDim {
+            progress: 0.0,
+            amplitude,
+            frequency,
+            aspect_ratio: 1.0,
+            center: mint::Vector2 {
+                x: center_x,
+                y: center_y,
+            },
+        };
+
+        // the drop is fast, then the waves slow down
+        ShaderTransition::with_shader(
+            "/ripple.frag.wgsl",
+            dim,
+            Box::new(StepsVelocity::new(vec![1.6, 1.0, 0.6])),
+        )
+    }
+
+    /// Parameters:
+    /// - amplitude: the displacement of the waves, relative to the height of the image
+    ///   (default 0.03)
+    /// - frequency: the number of waves per height of the image (default 12)
+    /// - center_x, center_y: where the drop falls, relative to the size of the image
+    ///   (default 0.5)
+    /// - easing: the easing curve, see Easing (default fast at the start)
+    pub fn from_params(params: &TransitionParams) -> GameResult<Ripple> {
+        Ripple::new(
+            params.get("amplitude", 0.03)?,
+            params.get("frequency", 12.0)?,
+            params.get("center_x", 0.5)?,
+            params.get("center_y", 0.5)?,
+        )
+        .with_params(params)
+    }
+}
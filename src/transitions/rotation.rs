@@ -0,0 +1,355 @@
+use std::f32::consts::PI;
+use std::str::FromStr;
+
+use crevice::std140::AsStd140;
+use ggez::graphics::{Canvas, Color, DrawMode, DrawParam, Drawable, Image};
+use ggez::*;
+
+use crate::ggez_utils::{draw_rect, Point2};
+use crate::transitions::params::TransitionParams;
+use crate::transitions::shaders::Shaders;
+use crate::transitions::transition::{ImagePair, Transition};
+use crate::velocity::*;
+
+/// how much the progress advances every frame, at velocity 1
+const STEP: f32 = 0.01;
+
+/// the distance of the camera from the screen, relative to the height of the image
+const DISTANCE: f32 = 2.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RotationKind {
+    /// the images are two adjacent faces of a cube, that turns by 90 degrees
+    Cube,
+    /// the images are the two sides of a card, that turns by 180 degrees
+    Flip,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RotationAxis {
+    /// the images turn around the vertical axis, so they move sideways
+    Vertical,
+    /// the images turn around the horizontal axis, so they move up or down
+    Horizontal,
+}
+
+impl RotationAxis {
+    /// The value of the axis in the shader.
+    fn to_uniform(self) -> u32 {
+        match self {
+            RotationAxis::Vertical => 0,
+            RotationAxis::Horizontal => 1,
+        }
+    }
+}
+
+impl FromStr for RotationAxis {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "vertical" => Ok(RotationAxis::Vertical),
+            "horizontal" => Ok(RotationAxis::Horizontal),
+            _ => Err("expected one of vertical, horizontal".to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RotationDirection {
+    /// the old image leaves to the left, or to the top
+    Forward,
+    /// the old image leaves to the right, or to the bottom
+    Backward,
+}
+
+impl RotationDirection {
+    /// The sign of the angles, the shader turns the positive ones to the right and to the bottom.
+    fn sign(self) -> f32 {
+        match self {
+            RotationDirection::Forward => -1.0,
+            RotationDirection::Backward => 1.0,
+        }
+    }
+}
+
+impl FromStr for RotationDirection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "forward" => Ok(RotationDirection::Forward),
+            "backward" => Ok(RotationDirection::Backward),
+            _ => Err("expected one of forward, backward".to_string()),
+        }
+    }
+}
+
+// Define the input struct for our shader.
+#[derive(AsStd140)]
+struct Dim {
+    angle: f32,
+    depth: f32,
+    distance: f32,
+    aspect_ratio: f32,
+    axis: u32,
+    push: f32,
+}
+
+/// The geometry of the faces at the given progress, half_extent is half the size of the image
+/// across the axis, relative to the height of the image.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Faces {
+    /// the angles of the old and of the new image, without the sign of the direction
+    angles: [f32; 2],
+    /// the distance of the faces from the axis
+    depth: f32,
+    /// how much the faces are moved away from the camera, so they never come out of the screen
+    push: f32,
+}
+
+fn faces(kind: RotationKind, progress: f32, half_extent: f32) -> Faces {
+    match kind {
+        RotationKind::Cube => {
+            let angle = progress * PI / 2.0;
+            Faces {
+                angles: [angle, angle - PI / 2.0],
+                depth: half_extent,
+                // the nearest edge of the cube stays on the screen
+                push: half_extent * (angle.sin() + angle.cos() - 1.0),
+            }
+        }
+        RotationKind::Flip => {
+            let angle = progress * PI;
+            Faces {
+                angles: [angle, angle - PI],
+                depth: 0.0,
+                // the nearest edge of the card stays on the screen
+                push: half_extent * angle.sin(),
+            }
+        }
+    }
+}
+
+/// True if the face, rotated by angle, is turned toward the camera, it's the test done by the
+/// vertex shader, the dot product of the normal with the direction from the face to the camera.
+#[cfg(test)]
+fn front_facing(angle: f32, depth: f32, push: f32) -> bool {
+    angle.cos() * (DISTANCE + depth + push) > depth
+}
+
+/// The images are drawn as textured quads, in perspective, turning around an axis, the faces
+/// turned away from the camera are not drawn.
+pub struct Rotation {
+    kind: RotationKind,
+    axis: RotationAxis,
+    direction: RotationDirection,
+    images: ImagePair,
+    ended: bool,
+    progress: Progress,
+    shader: Option<graphics::Shader>,
+    velocity: Box<dyn Velocity>,
+}
+
+impl Rotation {
+    pub fn new(kind: RotationKind, axis: RotationAxis, direction: RotationDirection) -> Rotation {
+        Rotation {
+            kind,
+            axis,
+            direction,
+            images: ImagePair::default(),
+            ended: true,
+            progress: Progress::default(),
+            shader: None,
+            velocity: Box::new(StepsVelocity::new(vec![0.5, 1.5, 0.5])),
+        }
+    }
+
+    /// Parameters, the kind is the name of the transition, cube or flip:
+    /// - axis: vertical (default), the images move sideways, horizontal, they move up or down
+    /// - direction: forward (default), the old image leaves to the left or to the top, backward
+    /// - easing: the easing curve, see Easing (default slow at the start and at the end)
+    pub fn from_params(params: &TransitionParams) -> GameResult<Rotation> {
+        let kind = match params.name() {
+            "cube" => RotationKind::Cube,
+            "flip" => RotationKind::Flip,
+            name => return Err(GameError::CustomError(format!("Unknown rotation {}", name))),
+        };
+
+        let mut rotation = Rotation::new(
+            kind,
+            params.get("axis", RotationAxis::Vertical)?,
+            params.get("direction", RotationDirection::Forward)?,
+        );
+
+        if let Some(easing) = params.get_opt::<Easing>("easing")? {
+            rotation.velocity = Box::new(easing);
+        }
+
+        Ok(rotation)
+    }
+
+    /// Draws the faces at the given progress.
+    fn draw_frame(&self, ctx: &mut Context, canvas: &mut Canvas, progress: f32) -> GameResult {
+        if let (Some(image), Some(last_image), Some(shader)) =
+            (self.images.image(), self.images.last_image(), &self.shader)
+        {
+            let width = image.width() as f32;
+            let height = image.height() as f32;
+            let aspect_ratio = width / height;
+
+            let half_extent = match self.axis {
+                RotationAxis::Vertical => aspect_ratio / 2.0,
+                RotationAxis::Horizontal => 0.5,
+            };
+
+            let faces = faces(self.kind, progress, half_extent);
+
+            // what is around the faces
+            draw_rect(
+                ctx,
+                canvas,
+                0.0,
+                0.0,
+                width,
+                height,
+                &Color::BLACK,
+                DrawMode::fill(),
+            )?;
+
+            canvas.set_shader(shader);
+
+            for (face, angle) in [last_image, image].iter().zip(faces.angles) {
+                let dim = Dim {
+                    angle: angle * self.direction.sign(),
+                    depth: faces.depth,
+                    distance: DISTANCE,
+                    aspect_ratio,
+                    axis: self.axis.to_uniform(),
+                    push: faces.push,
+                };
+
+                let shader_params = graphics::ShaderParamsBuilder::new(&dim).build(ctx);
+                canvas.set_shader_params(&shader_params);
+
+                // the old image is stretched to the size of the new one, if the window is resized
+                let param = DrawParam::new()
+                    .dest(Point2::new(0.0, 0.0))
+                    .scale([width / face.width() as f32, height / face.height() as f32]);
+                face.draw(canvas, param);
+            }
+
+            canvas.set_default_shader();
+        }
+
+        Ok(())
+    }
+}
+
+impl Transition for Rotation {
+    fn prepare(&mut self, ctx: &mut Context, shaders: &mut Shaders) -> GameResult {
+        self.shader =
+            Some(shaders.get_with_vertex(ctx, "/rotation.vert.wgsl", "/rotation.frag.wgsl")?);
+        Ok(())
+    }
+
+    fn draw(&mut self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult<bool> {
+        if self.ended {
+            return Ok(false);
+        }
+
+        self.progress.advance(self.velocity.as_ref(), STEP);
+
+        self.draw_frame(ctx, canvas, self.progress.value)?;
+
+        if self.progress.ended() {
+            self.ended = true;
+        }
+
+        Ok(!self.ended)
+    }
+
+    fn reversible(&self) -> bool {
+        true
+    }
+
+    fn draw_at(&mut self, ctx: &mut Context, canvas: &mut Canvas, progress: f32) -> GameResult {
+        self.draw_frame(ctx, canvas, progress)
+    }
+
+    fn skip_to_end(&mut self, _ctx: &mut Context) -> GameResult {
+        self.ended = true;
+        Ok(())
+    }
+
+    fn update_image(&mut self, ctx: &mut Context, image: Image) {
+        let from = self.images.next_from(ctx, &image);
+        self.set_images(ctx, from, image);
+    }
+
+    fn set_images(&mut self, _ctx: &mut Context, from: Image, to: Image) {
+        self.images.set(from, to);
+        self.ended = false;
+        self.progress = Progress::default();
+    }
+
+    fn resize(&mut self, _ctx: &mut Context, image: Image) {
+        self.images.resize(image);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn visible(faces: Faces) -> [bool; 2] {
+        [
+            front_facing(faces.angles[0], faces.depth, faces.push),
+            front_facing(faces.angles[1], faces.depth, faces.push),
+        ]
+    }
+
+    #[test]
+    fn test_cube_faces() {
+        let half_extent = 16.0 / 9.0 / 2.0;
+
+        assert_eq!(
+            [true, false],
+            visible(faces(RotationKind::Cube, 0.0, half_extent))
+        );
+        // in the middle both faces are seen
+        assert_eq!(
+            [true, true],
+            visible(faces(RotationKind::Cube, 0.5, half_extent))
+        );
+        assert_eq!(
+            [false, true],
+            visible(faces(RotationKind::Cube, 1.0, half_extent))
+        );
+    }
+
+    #[test]
+    fn test_flip_shows_one_side() {
+        for i in 0..=100 {
+            let progress = i as f32 / 100.0;
+
+            if i == 50 {
+                // edge on
+                continue;
+            }
+
+            let [old, new] = visible(faces(RotationKind::Flip, progress, 0.5));
+            assert_eq!(progress < 0.5, old, "{}", progress);
+            assert_eq!(progress > 0.5, new, "{}", progress);
+        }
+    }
+
+    #[test]
+    fn test_push_keeps_the_faces_behind_the_screen() {
+        for kind in [RotationKind::Cube, RotationKind::Flip] {
+            assert_eq!(0.0, faces(kind, 0.0, 0.5).push);
+            assert!(faces(kind, 0.5, 0.5).push > 0.0);
+            assert!(faces(kind, 1.0, 0.5).push.abs() < 1e-6);
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use crevice::std140::AsStd140;
+use ggez::graphics::{Canvas, DrawParam, Drawable, Image, Sampler};
+use ggez::*;
+
+use crate::ggez_utils::Point2;
+use crate::transitions::params::TransitionParams;
+use crate::transitions::shaders::Shaders;
+use crate::transitions::transition::{ImagePair, Transition};
+use crate::velocity::*;
+
+/// how much the progress advances every frame, at velocity 1
+const STEP: f32 = 0.01;
+
+/// The uniforms of a ShaderTransition: the transition sets the progress and the aspect ratio, the
+/// other fields are the parameters of the effect.
+pub trait ShaderDim: AsStd140 {
+    fn set_progress(&mut self, progress: f32);
+
+    fn set_aspect_ratio(&mut self, aspect_ratio: f32);
+}
+
+/// A transition drawn by a fragment shader: the new image is drawn, and the old one is given to
+/// the shader as a texture, while the progress goes from 0 to 1 with the velocity.
+pub struct ShaderTransition<D> {
+    /// the path of the shader in the resources
+    path: &'static str,
+    images: ImagePair,
+    ended: bool,
+    shader: Option<graphics::Shader>,
+    progress: Progress,
+    velocity: Box<dyn Velocity>,
+    dim: D,
+}
+
+impl<D: ShaderDim> ShaderTransition<D> {
+    /// velocity is the default one, the easing parameter replaces it.
+    pub fn with_shader(
+        path: &'static str,
+        dim: D,
+        velocity: Box<dyn Velocity>,
+    ) -> ShaderTransition<D> {
+        ShaderTransition {
+            path,
+            images: ImagePair::default(),
+            ended: true,
+            shader: None,
+            progress: Progress::default(),
+            velocity,
+            dim,
+        }
+    }
+
+    /// Reads the parameters that all the shader transitions have:
+    /// - easing: the easing curve, see Easing
+    pub fn with_params(mut self, params: &TransitionParams) -> GameResult<ShaderTransition<D>> {
+        if let Some(easing) = params.get_opt::<Easing>("easing")? {
+            self.velocity = Box::new(easing);
+        }
+
+        Ok(self)
+    }
+
+    /// Draws the frame at the progress in the parameters of the shader.
+    fn draw_frame(&self, ctx: &mut Context, canvas: &mut Canvas) {
+        if let (Some(image), Some(last_image), Some(shader)) =
+            (self.images.image(), self.images.last_image(), &self.shader)
+        {
+            canvas.set_shader(shader);
+            let shader_params = graphics::ShaderParamsBuilder::new(&self.dim)
+                .images(&[last_image], &[Sampler::linear_clamp()], false)
+                .build(ctx);
+            canvas.set_shader_params(&shader_params);
+
+            image.draw(canvas, DrawParam::new().dest(Point2::new(0.0, 0.0)));
+            canvas.set_default_shader();
+        }
+    }
+}
+
+impl<D: ShaderDim> Transition for ShaderTransition<D> {
+    fn prepare(&mut self, ctx: &mut Context, shaders: &mut Shaders) -> GameResult {
+        self.shader = Some(shaders.get(ctx, self.path)?);
+        Ok(())
+    }
+
+    fn draw(&mut self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult<bool> {
+        if self.ended {
+            return Ok(false);
+        }
+
+        self.progress.advance(self.velocity.as_ref(), STEP);
+        self.dim.set_progress(self.progress.value);
+
+        self.draw_frame(ctx, canvas);
+
+        if self.progress.ended() {
+            self.ended = true;
+        }
+
+        Ok(!self.ended)
+    }
+
+    fn reversible(&self) -> bool {
+        true
+    }
+
+    fn draw_at(&mut self, ctx: &mut Context, canvas: &mut Canvas, progress: f32) -> GameResult {
+        self.dim.set_progress(progress);
+        self.draw_frame(ctx, canvas);
+        Ok(())
+    }
+
+    fn skip_to_end(&mut self, _ctx: &mut Context) -> GameResult {
+        self.ended = true;
+        Ok(())
+    }
+
+    fn update_image(&mut self, ctx: &mut Context, image: Image) {
+        let from = self.images.next_from(ctx, &image);
+        self.set_images(ctx, from, image);
+    }
+
+    fn set_images(&mut self, _ctx: &mut Context, from: Image, to: Image) {
+        self.dim
+            .set_aspect_ratio(to.width() as f32 / to.height() as f32);
+        self.images.set(from, to);
+        self.ended = false;
+        self.progress = Progress::default();
+        self.dim.set_progress(0.0);
+    }
+
+    fn resize(&mut self, _ctx: &mut Context, image: Image) {
+        self.dim
+            .set_aspect_ratio(image.width() as f32 / image.height() as f32);
+        self.images.resize(image);
+    }
+}
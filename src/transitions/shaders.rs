@@ -0,0 +1,81 @@
+use std::collections::HashMap;
+
+use ggez::graphics::Shader;
+use ggez::*;
+
+/// The vertex shader of the transitions that draw flat images.
+const VERTEX_PATH: &str = "/simple.vert.wgsl";
+
+/// The shaders of the transitions, built once and shared by all the transitions that use the
+/// same fragment shader.
+/// Shaders are cheap to clone, they hold references to the compiled modules.
+#[derive(Default)]
+pub struct Shaders {
+    shaders: HashMap<String, Shader>,
+}
+
+impl Shaders {
+    pub fn new() -> Shaders {
+        Shaders::default()
+    }
+
+    /// The shader with the fragment shader at the given path, in the resources.
+    pub fn get(&mut self, ctx: &mut Context, fragment_path: &str) -> GameResult<Shader> {
+        if let Some(shader) = self.shaders.get(fragment_path) {
+            return Ok(shader.clone());
+        }
+
+        let shader = graphics::ShaderBuilder::new()
+            .vertex_path(VERTEX_PATH)
+            .fragment_path(fragment_path)
+            .build(ctx)?;
+
+        self.shaders
+            .insert(fragment_path.to_string(), shader.clone());
+        Ok(shader)
+    }
+
+    /// The shader with its own vertex shader, both at the given paths, in the resources.
+    pub fn get_with_vertex(
+        &mut self,
+        ctx: &mut Context,
+        vertex_path: &str,
+        fragment_path: &str,
+    ) -> GameResult<Shader> {
+        let key = format!("{} {}", vertex_path, fragment_path);
+
+        if let Some(shader) = self.shaders.get(&key) {
+            return Ok(shader.clone());
+        }
+
+        let shader = graphics::ShaderBuilder::new()
+            .vertex_path(vertex_path)
+            .fragment_path(fragment_path)
+            .build(ctx)?;
+
+        self.shaders.insert(key, shader.clone());
+        Ok(shader)
+    }
+
+    /// The shader with the given code of the fragment shader, key identifies the code, usually
+    /// it's the path of the file the code has been generated from.
+    pub fn get_code(&mut self, ctx: &mut Context, key: &str, code: &str) -> GameResult<Shader> {
+        if let Some(shader) = self.shaders.get(key) {
+            return Ok(shader.clone());
+        }
+
+        self.rebuild_code(ctx, key, code)
+    }
+
+    /// Builds the shader with the given code of the fragment shader, even if there's already
+    /// one with the same key, that is replaced, like when the file of the code has changed.
+    pub fn rebuild_code(&mut self, ctx: &mut Context, key: &str, code: &str) -> GameResult<Shader> {
+        let shader = graphics::ShaderBuilder::new()
+            .vertex_path(VERTEX_PATH)
+            .fragment_code(code)
+            .build(ctx)?;
+
+        self.shaders.insert(key.to_string(), shader.clone());
+        Ok(shader)
+    }
+}
@@ -0,0 +1,324 @@
+use ggez::graphics::{Canvas, DrawParam, Drawable, Image, Mesh, MeshData, Vertex};
+use ggez::*;
+use rand::Rng;
+
+use crate::ggez_utils::{clip, Point2, Vector2};
+use crate::random::{random_seed, shared_rng, SharedRng};
+use crate::transitions::params::TransitionParams;
+use crate::transitions::transition::{ImagePair, Transition};
+
+/// the acceleration of the shards, relative to the height of the image, per frame
+const GRAVITY: f32 = 0.0008;
+/// the speed of the shards away from the impact, relative to the height of the image, per frame
+const SPEED: f32 = 0.004;
+/// the frames between the fall of the shards near the impact and the farthest ones
+const MAX_DELAY: f32 = 30.0;
+/// the transition ends anyway after this number of frames
+const MAX_FRAMES: u32 = 300;
+
+/// A piece of the old image, a convex polygon.
+struct Shard {
+    /// the vertices in the image, they are also the texture coordinates
+    polygon: Vec<Point2>,
+    centroid: Point2,
+    offset: Vector2,
+    velocity: Vector2,
+    angle: f32,
+    angular_velocity: f32,
+    /// the frames before the shard starts to fall
+    delay: u32,
+}
+
+impl Shard {
+    fn update(&mut self, gravity: f32) {
+        if self.delay > 0 {
+            self.delay -= 1;
+            return;
+        }
+
+        self.velocity.y += gravity;
+        self.offset += self.velocity;
+        self.angle += self.angular_velocity;
+    }
+
+    /// The vertices rotated around the centroid and moved by the offset.
+    fn transformed(&self) -> Vec<Point2> {
+        let (sin, cos) = self.angle.sin_cos();
+
+        self.polygon
+            .iter()
+            .map(|p| {
+                let v = p - self.centroid;
+                self.centroid
+                    + Vector2::new(v.x * cos - v.y * sin, v.x * sin + v.y * cos)
+                    + self.offset
+            })
+            .collect()
+    }
+
+    fn is_gone(&self, height: f32) -> bool {
+        self.transformed().iter().all(|p| p.y > height)
+    }
+
+    fn to_mesh_data(&self, width: f32, height: f32) -> (Vec<Vertex>, Vec<u32>) {
+        let vertices = self
+            .transformed()
+            .iter()
+            .zip(self.polygon.iter())
+            .map(|(p, original)| Vertex {
+                position: [p.x, p.y],
+                uv: [original.x / width, original.y / height],
+                color: [1.0, 1.0, 1.0, 1.0],
+            })
+            .collect::<Vec<_>>();
+
+        // the polygon is convex, so it can be drawn as a triangle fan
+        let mut indices = Vec::new();
+        for i in 2..vertices.len() as u32 {
+            indices.extend_from_slice(&[0, i - 1, i]);
+        }
+
+        (vertices, indices)
+    }
+}
+
+/// The Voronoi cells of the sites, clipped to the rectangle of the image: each cell is the
+/// rectangle clipped by the bisectors between its site and all the others.
+fn voronoi_cells(sites: &[Point2], width: f32, height: f32) -> Vec<Vec<Point2>> {
+    sites
+        .iter()
+        .enumerate()
+        .map(|(i, site)| {
+            let mut polygon = vec![
+                Point2::new(0.0, 0.0),
+                Point2::new(width, 0.0),
+                Point2::new(width, height),
+                Point2::new(0.0, height),
+            ];
+
+            for (j, other) in sites.iter().enumerate() {
+                if i == j || polygon.is_empty() {
+                    continue;
+                }
+
+                let normal = other - site;
+                let middle = nalgebra::center(site, other);
+                polygon = clip(&polygon, normal, middle.coords.dot(&normal));
+            }
+
+            polygon
+        })
+        .filter(|polygon| polygon.len() >= 3)
+        .collect()
+}
+
+/// Breaks the image in shards, they are smaller near the impact, and they start to fall from
+/// there.
+fn shatter<R: Rng>(rng: &mut R, n: usize, width: f32, height: f32, impact: Point2) -> Vec<Shard> {
+    let sites = (0..n)
+        .map(|_| {
+            let p = Point2::new(rng.gen_range(0.0, width), rng.gen_range(0.0, height));
+            // the sites are moved toward the impact, so there are more shards near it
+            impact + (p - impact) * rng.gen_range(0.2, 1.0)
+        })
+        .collect::<Vec<_>>();
+
+    let diagonal = (width * width + height * height).sqrt();
+
+    voronoi_cells(&sites, width, height)
+        .into_iter()
+        .map(|polygon| {
+            let centroid = Point2::from(
+                polygon.iter().map(|p| p.coords).sum::<Vector2>() / polygon.len() as f32,
+            );
+
+            let away = centroid - impact;
+            let distance = away.norm();
+            let direction = if distance > 0.0 {
+                away / distance
+            } else {
+                Vector2::new(0.0, -1.0)
+            };
+
+            let speed = SPEED * height * rng.gen_range(0.5, 1.5);
+
+            Shard {
+                polygon,
+                centroid,
+                offset: Vector2::new(0.0, 0.0),
+                velocity: direction * speed - Vector2::new(0.0, SPEED * height),
+                angle: 0.0,
+                angular_velocity: rng.gen_range(-0.05, 0.05),
+                delay: (distance / diagonal * MAX_DELAY) as u32,
+            }
+        })
+        .collect()
+}
+
+/// The old image breaks in Voronoi shards, that fall and rotate away revealing the new one.
+pub struct Shatter {
+    n_shards: usize,
+    /// the seed of the transition, its generator is not replaced by the one of the slideshow
+    seed: Option<u64>,
+    rng: SharedRng,
+    shards: Vec<Shard>,
+    images: ImagePair,
+    frames: u32,
+}
+
+impl Shatter {
+    /// seed makes the shards the same at every run, otherwise they come from the generator of
+    /// the slideshow.
+    pub fn new(shards: usize, seed: Option<u64>) -> Shatter {
+        Shatter {
+            n_shards: shards.max(1),
+            seed,
+            rng: shared_rng(seed.unwrap_or_else(random_seed)),
+            shards: Vec::new(),
+            images: ImagePair::default(),
+            frames: MAX_FRAMES,
+        }
+    }
+
+    /// Parameters:
+    /// - shards: the number of shards (default 40)
+    /// - seed: the seed of the random generator, for reproducible shards
+    pub fn from_params(params: &TransitionParams) -> GameResult<Shatter> {
+        Ok(Shatter::new(
+            params.get("shards", 40)?,
+            params.get_opt("seed")?,
+        ))
+    }
+}
+
+impl Transition for Shatter {
+    fn set_rng(&mut self, rng: SharedRng) {
+        if self.seed.is_none() {
+            self.rng = rng;
+        }
+    }
+
+    fn draw(&mut self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult<bool> {
+        if self.frames >= MAX_FRAMES {
+            return Ok(false);
+        }
+
+        self.frames += 1;
+
+        if let (Some(image), Some(last_image)) = (self.images.image(), self.images.last_image()) {
+            let width = image.width() as f32;
+            let height = image.height() as f32;
+
+            image.draw(canvas, DrawParam::new().dest(Point2::new(0.0, 0.0)));
+
+            for shard in self.shards.iter_mut() {
+                shard.update(GRAVITY * height);
+            }
+
+            self.shards.retain(|shard| !shard.is_gone(height));
+
+            for shard in self.shards.iter() {
+                let (vertices, indices) = shard.to_mesh_data(width, height);
+
+                let mesh = Mesh::from_data(
+                    ctx,
+                    MeshData {
+                        vertices: &vertices,
+                        indices: &indices,
+                    },
+                );
+
+                canvas.draw_textured_mesh(mesh, last_image.clone(), DrawParam::default());
+            }
+        }
+
+        if self.shards.is_empty() {
+            self.frames = MAX_FRAMES;
+        }
+
+        Ok(self.frames < MAX_FRAMES)
+    }
+
+    fn skip_to_end(&mut self, _ctx: &mut Context) -> GameResult {
+        self.shards.clear();
+        self.frames = MAX_FRAMES;
+        Ok(())
+    }
+
+    fn update_image(&mut self, ctx: &mut Context, image: Image) {
+        let from = self.images.next_from(ctx, &image);
+        self.set_images(ctx, from, image);
+    }
+
+    fn set_images(&mut self, _ctx: &mut Context, from: Image, to: Image) {
+        let width = to.width() as f32;
+        let height = to.height() as f32;
+
+        let mut rng = self.rng.borrow_mut();
+
+        let impact = Point2::new(
+            rng.gen_range(0.3, 0.7) * width,
+            rng.gen_range(0.3, 0.7) * height,
+        );
+
+        self.shards = shatter(&mut *rng, self.n_shards, width, height, impact);
+        self.images.set(from, to);
+        self.frames = 0;
+    }
+
+    fn resize(&mut self, _ctx: &mut Context, image: Image) {
+        // the shards do not fit anymore, the new image is drawn once and the transition ends
+        self.shards.clear();
+        self.images.resize(image);
+        self.frames = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use crate::ggez_utils::polygon_area;
+
+    use super::*;
+
+    #[test]
+    fn test_cells_cover_the_image() {
+        let mut rng = StdRng::seed_from_u64(1);
+
+        let shards = shatter(&mut rng, 30, 200.0, 100.0, Point2::new(100.0, 50.0));
+
+        let total: f32 = shards.iter().map(|it| polygon_area(&it.polygon)).sum();
+        assert!((total - 200.0 * 100.0).abs() < 1.0, "{}", total);
+        assert_eq!(30, shards.len());
+    }
+
+    #[test]
+    fn test_same_seed_same_shards() {
+        let centroids = |seed| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            shatter(&mut rng, 10, 200.0, 100.0, Point2::new(100.0, 50.0))
+                .iter()
+                .map(|it| it.centroid)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(centroids(42), centroids(42));
+        assert_ne!(centroids(42), centroids(43));
+    }
+
+    #[test]
+    fn test_shards_fall_away() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut shards = shatter(&mut rng, 20, 200.0, 100.0, Point2::new(100.0, 50.0));
+
+        for _ in 0..MAX_FRAMES {
+            for shard in shards.iter_mut() {
+                shard.update(GRAVITY * 100.0);
+            }
+        }
+
+        assert!(shards.iter().all(|it| it.is_gone(100.0)));
+    }
+}
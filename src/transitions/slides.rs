@@ -1,75 +1,198 @@
-use ggez::graphics::{Canvas, DrawParam, Drawable, Image, Rect};
+use std::str::FromStr;
+
+use ggez::graphics::{Canvas, DrawParam, Image, Mesh, MeshData, Vertex};
 use ggez::*;
 
-use crate::ggez_utils::Point2;
+use crate::ggez_utils::{clip, Point2, Vector2};
+use crate::transitions::params::TransitionParams;
 use crate::transitions::transition::*;
 use crate::velocity::*;
 
 const VELOCITY: f32 = 15.0;
 
+/// the number of strips, when it is not given
+const DEFAULT_STRIPS: u32 = 8;
+
+/// How the image is divided into strips.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SlidesOrientation {
+    /// rows, sliding horizontally
+    Horizontal,
+    /// columns, sliding vertically
+    Vertical,
+    /// 45° bands, sliding along the band
+    Diagonal,
+}
+
+impl FromStr for SlidesOrientation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "horizontal" => Ok(SlidesOrientation::Horizontal),
+            "vertical" => Ok(SlidesOrientation::Vertical),
+            "diagonal" => Ok(SlidesOrientation::Diagonal),
+            _ => Err("expected one of horizontal, vertical, diagonal".to_string()),
+        }
+    }
+}
+
+/// The side the strips come from.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SlidesFrom {
+    /// from one side and the opposite one, strip by strip
+    Alternate,
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+impl FromStr for SlidesFrom {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "alternate" => Ok(SlidesFrom::Alternate),
+            "left" => Ok(SlidesFrom::Left),
+            "right" => Ok(SlidesFrom::Right),
+            "top" => Ok(SlidesFrom::Top),
+            "bottom" => Ok(SlidesFrom::Bottom),
+            _ => Err("expected one of alternate, left, right, top, bottom".to_string()),
+        }
+    }
+}
+
 pub struct Slides {
     n_slides: u32,
+    orientation: SlidesOrientation,
+    from: SlidesFrom,
+    /// the number of frames between the start of a strip and the start of the next one
+    stagger: u32,
     slides: Vec<Slide>,
     image: Option<Image>,
 }
 
 impl Slides {
-    pub fn new(slides: u32) -> Slides {
+    pub fn new(
+        slides: u32,
+        orientation: SlidesOrientation,
+        from: SlidesFrom,
+        stagger: u32,
+    ) -> Slides {
         Slides {
-            n_slides: slides,
+            n_slides: slides.max(1),
+            orientation,
+            from,
+            stagger,
             slides: Vec::new(),
             image: None,
         }
     }
-}
 
-enum Direction {
-    Right,
-    Left,
+    /// Parameters, slide is an alias of slides with one strip:
+    /// - strips: the number of strips (default 8)
+    /// - orientation: horizontal (default), vertical, diagonal
+    /// - from: alternate (default), left, right for horizontal and diagonal strips,
+    ///   top, bottom for vertical strips
+    /// - stagger: the number of frames between the start of a strip and the next one (default 0)
+    pub fn from_params(params: &TransitionParams) -> GameResult<Slides> {
+        let default_strips = match params.name() {
+            "slide" => 1,
+            _ => DEFAULT_STRIPS,
+        };
+
+        let strips = params.get("strips", default_strips)?;
+        let orientation = params.get("orientation", SlidesOrientation::Horizontal)?;
+        let from = params.get("from", SlidesFrom::Alternate)?;
+        let stagger = params.get("stagger", 0)?;
+
+        let valid = match (orientation, from) {
+            (_, SlidesFrom::Alternate) => true,
+            (SlidesOrientation::Vertical, SlidesFrom::Top | SlidesFrom::Bottom) => true,
+            (SlidesOrientation::Vertical, _) => false,
+            (_, SlidesFrom::Left | SlidesFrom::Right) => true,
+            _ => false,
+        };
+
+        if !valid {
+            return Err(GameError::CustomError(format!(
+                "{:?} {} strips cannot come from {:?}",
+                orientation,
+                params.name(),
+                from
+            )));
+        }
+
+        Ok(Slides::new(strips, orientation, from, stagger))
+    }
+
+    /// The normal of the bands and the direction of the movement of the i-th strip.
+    fn axes(&self, i: u32) -> (Vector2, Vector2) {
+        let (normal, forward) = match self.orientation {
+            SlidesOrientation::Horizontal => (Vector2::new(0.0, 1.0), Vector2::new(1.0, 0.0)),
+            SlidesOrientation::Vertical => (Vector2::new(1.0, 0.0), Vector2::new(0.0, 1.0)),
+            SlidesOrientation::Diagonal => (
+                Vector2::new(1.0, 1.0).normalize(),
+                Vector2::new(1.0, -1.0).normalize(),
+            ),
+        };
+
+        let backward = match self.from {
+            SlidesFrom::Alternate => i % 2 == 1,
+            SlidesFrom::Left | SlidesFrom::Top => false,
+            SlidesFrom::Right | SlidesFrom::Bottom => true,
+        };
+
+        if backward {
+            (normal, -forward)
+        } else {
+            (normal, forward)
+        }
+    }
 }
 
 struct Slide {
     i_width: u32,
     i_height: u32,
-    x: f32,
-    y: f32,
-    width: f32,
-    height: f32,
+    /// the strip is the part of the image where the projection on the normal is between min and
+    /// max
+    normal: Vector2,
+    min: f32,
+    max: f32,
+    /// the direction of the movement
+    direction: Vector2,
+    /// the distance to travel to reach the final position
+    length: f32,
+    distance: f32,
     vx: f32,
-    vy: i32,
-    direction: Direction,
+    /// the number of frames to wait before starting
+    delay: u32,
     ended: bool,
     velocity: Box<dyn Velocity>,
 }
 
 impl Slide {
-    fn left(n_slides: u32, i_width: u32, i_height: u32, y: f32) -> Slide {
-        Slide {
-            i_width,
-            i_height,
-            x: i_width as f32,
-            y,
-            width: 0.0,
-            height: i_height as f32 / n_slides as f32,
-            vx: VELOCITY,
-            vy: 0,
-            direction: Direction::Left,
-            ended: false,
-            velocity: Slide::velocity(),
-        }
-    }
-
-    fn right(n_slides: u32, i_width: u32, i_height: u32, y: f32) -> Slide {
+    fn new(
+        i_width: u32,
+        i_height: u32,
+        normal: Vector2,
+        min: f32,
+        max: f32,
+        direction: Vector2,
+        delay: u32,
+    ) -> Slide {
         Slide {
             i_width,
             i_height,
-            x: 0.0,
-            y: y as f32,
-            width: 0.0,
-            height: i_height as f32 / n_slides as f32,
+            normal,
+            min,
+            max,
+            direction,
+            length: (i_width as f32 * direction.x).abs() + (i_height as f32 * direction.y).abs(),
+            distance: 0.0,
             vx: VELOCITY,
-            vy: 0,
-            direction: Direction::Right,
+            delay,
             ended: false,
             velocity: Slide::velocity(),
         }
@@ -80,40 +203,70 @@ impl Slide {
     }
 
     fn update(&mut self) {
-        self.width += self.vx;
-
-        match self.direction {
-            Direction::Left => self.x -= self.vx,
-            _ => {}
+        if self.delay > 0 {
+            self.delay -= 1;
+            return;
         }
 
-        if self.vx < 0.0 {
-            self.vx = 0.1;
-        }
+        self.distance += self.vx;
 
-        if self.width >= self.i_width as f32 {
+        if self.distance >= self.length {
             self.ended = true;
-            self.width = self.i_width as f32;
-            self.x = 0.0;
+            self.distance = self.length;
         }
 
-        self.vx = self.velocity.get_velocity(self.width / self.i_width as f32) * VELOCITY;
+        self.vx = self.velocity.get_velocity(self.distance / self.length) * VELOCITY;
     }
 
-    fn to_rect(&self) -> Rect {
-        Rect::new(
-            self.x / self.i_width as f32,
-            self.y / self.i_height as f32,
-            self.width / self.i_width as f32,
-            self.height / self.i_height as f32,
-        )
+    /// The translation of the image, it's zero at the end.
+    fn offset(&self) -> Vector2 {
+        self.direction * (self.distance - self.length)
     }
 
-    fn to_point(&self) -> Point2 {
-        match self.direction {
-            Direction::Left => Point2::new(0.0, self.y),
-            _ => Point2::new(self.i_width as f32 - self.width, self.y),
+    /// The visible part of the strip: the strip, inside the screen, of the translated image.
+    fn polygon(&self) -> Vec<Point2> {
+        let width = self.i_width as f32;
+        let height = self.i_height as f32;
+        let offset = self.offset();
+
+        let mut polygon = vec![
+            Point2::new(0.0, 0.0),
+            Point2::new(width, 0.0),
+            Point2::new(width, height),
+            Point2::new(0.0, height),
+        ];
+
+        polygon = clip(&polygon, -self.normal, -self.min);
+        polygon = clip(&polygon, self.normal, self.max);
+        polygon = clip(&polygon, Vector2::new(-1.0, 0.0), -offset.x);
+        polygon = clip(&polygon, Vector2::new(1.0, 0.0), width + offset.x);
+        polygon = clip(&polygon, Vector2::new(0.0, -1.0), -offset.y);
+        clip(&polygon, Vector2::new(0.0, 1.0), height + offset.y)
+    }
+
+    fn to_mesh_data(&self) -> (Vec<Vertex>, Vec<u32>) {
+        let offset = self.offset();
+
+        let vertices = self
+            .polygon()
+            .iter()
+            .map(|p| Vertex {
+                position: [p.x, p.y],
+                uv: [
+                    (p.x - offset.x) / self.i_width as f32,
+                    (p.y - offset.y) / self.i_height as f32,
+                ],
+                color: [1.0, 1.0, 1.0, 1.0],
+            })
+            .collect::<Vec<_>>();
+
+        // the polygon is convex, so it can be drawn as a triangle fan
+        let mut indices = Vec::new();
+        for i in 2..vertices.len() as u32 {
+            indices.extend_from_slice(&[0, i - 1, i]);
         }
+
+        (vertices, indices)
     }
 }
 
@@ -125,45 +278,62 @@ impl Transition for Slides {
 
         let mut ended = true;
 
-        match &self.image {
-            Some(i) => {
-                for slide in self.slides.iter_mut() {
-                    if !slide.ended {
-                        slide.update();
-
-                        let src = slide.to_rect();
-                        let dest = slide.to_point();
-
-                        // println!("slide {src:?} -> {dest}");
+        if let Some(i) = &self.image {
+            for slide in self.slides.iter_mut() {
+                if !slide.ended {
+                    slide.update();
 
-                        let draw_param = DrawParam::default().src(src).dest(dest);
+                    let (vertices, indices) = slide.to_mesh_data();
 
-                        i.draw(canvas, draw_param);
+                    if indices.is_empty() {
                         ended = false;
+                        continue;
                     }
+
+                    let mesh = Mesh::from_data(
+                        ctx,
+                        MeshData {
+                            vertices: &vertices,
+                            indices: &indices,
+                        },
+                    );
+
+                    canvas.draw_textured_mesh(mesh, i.clone(), DrawParam::default());
+                    ended = false;
                 }
             }
-            None => {}
         }
 
         Ok(!ended)
     }
 
-    fn update_image(&mut self, ctx: &mut Context, image: Image) {
+    fn skip_to_end(&mut self, _ctx: &mut Context) -> GameResult {
+        self.slides.clear();
+        Ok(())
+    }
+
+    fn update_image(&mut self, _ctx: &mut Context, image: Image) {
         // println!("update_image {},{}", image.width(), image.height());
 
         self.slides.clear();
 
-        let slide_height = image.height() as f32 / self.n_slides as f32;
-
         for i in 0..self.n_slides {
-            let y = i as f32 * slide_height;
-
-            let slide = if i % 2 == 0 {
-                Slide::left(self.n_slides, image.width(), image.height(), y)
-            } else {
-                Slide::right(self.n_slides, image.width(), image.height(), y)
-            };
+            let (normal, direction) = self.axes(i);
+
+            // the range of the projection of the image on the normal
+            let extent =
+                (image.width() as f32 * normal.x).abs() + (image.height() as f32 * normal.y).abs();
+            let slide_size = extent / self.n_slides as f32;
+
+            let slide = Slide::new(
+                image.width(),
+                image.height(),
+                normal,
+                i as f32 * slide_size,
+                (i + 1) as f32 * slide_size,
+                direction,
+                i * self.stagger,
+            );
 
             self.slides.push(slide);
         }
@@ -174,33 +344,146 @@ impl Transition for Slides {
 
 #[cfg(test)]
 mod tests {
+    use crate::ggez_utils::polygon_area;
+
     use super::*;
 
+    fn slides(orientation: SlidesOrientation, from: SlidesFrom, stagger: u32) -> Slides {
+        let mut slides = Slides::new(8, orientation, from, stagger);
+        let (normal, direction) = slides.axes(0);
+        slides.slides = (0..8)
+            .map(|i| {
+                Slide::new(
+                    100,
+                    80,
+                    normal,
+                    i as f32 * 10.0,
+                    (i + 1) as f32 * 10.0,
+                    direction,
+                    i * stagger,
+                )
+            })
+            .collect();
+        slides
+    }
+
     #[test]
     fn test_left_slide() {
-        let mut slide = Slide::left(8, 100, 50, 0.0);
-
-        assert_eq!(false, slide.ended);
+        let mut slide = Slide::new(
+            100,
+            50,
+            Vector2::new(0.0, 1.0),
+            0.0,
+            6.25,
+            Vector2::new(1.0, 0.0),
+            0,
+        );
+
+        assert!(!slide.ended);
+        assert!(slide.polygon().is_empty() || polygon_area(&slide.polygon()) == 0.0);
 
         slide.update();
 
-        assert_eq!(false, slide.ended);
-        assert_eq!(100.0 - VELOCITY, slide.x);
+        assert!(!slide.ended);
+        assert_eq!(-(100.0 - VELOCITY), slide.offset().x);
 
-        assert_eq!(VELOCITY, slide.width);
+        assert_eq!(VELOCITY, slide.distance);
+        assert_eq!(VELOCITY * 6.25, polygon_area(&slide.polygon()));
     }
 
     #[test]
     fn test_right_slide() {
-        let mut slide = Slide::right(8, 100, 50, 0.0);
-
-        assert_eq!(false, slide.ended);
+        let mut slide = Slide::new(
+            100,
+            50,
+            Vector2::new(0.0, 1.0),
+            0.0,
+            6.25,
+            Vector2::new(-1.0, 0.0),
+            0,
+        );
+
+        assert!(!slide.ended);
 
         slide.update();
 
-        assert_eq!(false, slide.ended);
-        assert_eq!(0.0, slide.x);
+        assert!(!slide.ended);
+        assert_eq!(100.0 - VELOCITY, slide.offset().x);
+        assert!(slide.polygon().iter().all(|p| p.x >= 100.0 - VELOCITY));
+
+        assert_eq!(VELOCITY, slide.distance);
+    }
+
+    #[test]
+    fn test_slide_ends_in_place() {
+        let mut slide = Slide::new(
+            100,
+            50,
+            Vector2::new(1.0, 0.0),
+            0.0,
+            12.5,
+            Vector2::new(0.0, 1.0),
+            0,
+        );
+
+        while !slide.ended {
+            slide.update();
+        }
+
+        assert_eq!(0.0, slide.offset().norm());
+        assert_eq!(12.5 * 50.0, polygon_area(&slide.polygon()));
+    }
+
+    #[test]
+    fn test_diagonal_strips_cover_the_image() {
+        let mut slides = Slides::new(5, SlidesOrientation::Diagonal, SlidesFrom::Alternate, 0);
+        let image_slides = (0..5)
+            .map(|i| {
+                let (normal, direction) = slides.axes(i);
+                let slide_size = (100.0 + 80.0) / 2f32.sqrt() / 5.0;
+                let mut slide = Slide::new(
+                    100,
+                    80,
+                    normal,
+                    i as f32 * slide_size,
+                    (i + 1) as f32 * slide_size,
+                    direction,
+                    0,
+                );
+                slide.distance = slide.length;
+                slide
+            })
+            .collect::<Vec<_>>();
+        slides.slides = image_slides;
+
+        let total = slides
+            .slides
+            .iter()
+            .map(|it| polygon_area(&it.polygon()))
+            .sum::<f32>();
+
+        assert!((total - 100.0 * 80.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_alternate_directions() {
+        let slides = Slides::new(8, SlidesOrientation::Horizontal, SlidesFrom::Alternate, 0);
+
+        assert_eq!(Vector2::new(1.0, 0.0), slides.axes(0).1);
+        assert_eq!(Vector2::new(-1.0, 0.0), slides.axes(1).1);
+    }
+
+    #[test]
+    fn test_stagger() {
+        let mut slides = slides(SlidesOrientation::Vertical, SlidesFrom::Top, 3);
+
+        for slide in slides.slides.iter_mut() {
+            slide.update();
+        }
 
-        assert_eq!(VELOCITY, slide.width);
+        assert_eq!(VELOCITY, slides.slides[0].distance);
+        assert_eq!(0.0, slides.slides[1].distance);
+        assert_eq!(2, slides.slides[1].delay);
+        assert_eq!(20, slides.slides[7].delay);
     }
 }
@@ -1,69 +1,257 @@
-use crevice::std140::AsStd140;
+use std::str::FromStr;
+
+use bytemuck::{Pod, Zeroable};
+use crevice::std140::Std140;
 use ggez::graphics::{Canvas, DrawParam, Drawable, Image};
 use ggez::*;
+use rand::Rng;
+
+use crate::ggez_utils::{Point2, Vector2};
+use crate::random::{random_seed, shared_rng, SharedRng};
+use crate::transitions::params::TransitionParams;
+use crate::transitions::shaders::Shaders;
+use crate::transitions::transition::{ImagePair, Transition};
+
+/// The maximum number of bubbles, it must match the size of the array in the shader.
+const MAX_BUBBLES: usize = 8;
+/// The radius of the bubble, when there's only one, relative to the shorter side of the image.
+const RADIUS: f32 = 0.5;
+
+/// Where the main bubble is.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SphereCenter {
+    /// a random position for each image
+    Random,
+    /// the position relative to the size of the image
+    Fixed(f32, f32),
+}
 
-use crate::ggez_utils::Point2;
-use crate::transitions::transition::Transition;
+/// Whether the sphere brings in the new image or takes out the old one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SphereMode {
+    /// the new image appears, refracted, in the growing bubbles
+    Grow,
+    /// the old image, refracted, shrinks out, revealing the new one
+    Shrink,
+}
+
+impl FromStr for SphereMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "grow" => Ok(SphereMode::Grow),
+            "shrink" => Ok(SphereMode::Shrink),
+            _ => Err("expected one of grow, shrink".to_string()),
+        }
+    }
+}
 
 // Define the input struct for our shader.
-#[derive(AsStd140)]
+// The layout is the std140 one, that cannot be derived for the array of the bubbles.
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
 struct Dim {
     rate: f32,
-    center_x: f32,
-    center_y: f32,
     radius: f32,
     aspect_ratio: f32,
     refractive_index: f32,
+    count: u32,
+    padding: [u32; 3],
+    /// x and y are the center relative to the size of the image, z is the radius relative to the
+    /// shorter side of the image, w is unused
+    bubbles: [[f32; 4]; MAX_BUBBLES],
+}
+
+unsafe impl Std140 for Dim {
+    const ALIGNMENT: usize = 16;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Bubble {
+    x: f32,
+    y: f32,
+    /// the radius at the end (or at the start for shrink mode) of the transition
+    radius: f32,
 }
 
 pub struct Sphere {
-    image: Option<Image>,
+    center: SphereCenter,
+    n_bubbles: usize,
+    mode: SphereMode,
+    bubbles: Vec<Bubble>,
+    images: ImagePair,
     ended: bool,
     shader: Option<graphics::Shader>,
     dim: Dim,
+    rng: SharedRng,
 }
 
 impl Sphere {
-    pub fn new() -> Sphere {
+    pub fn new(center: SphereCenter, bubbles: usize, mode: SphereMode) -> Sphere {
         let dim = Dim {
             rate: 1.0,
-            center_x: 0.5,
-            center_y: 0.5,
             radius: 0.5,
             aspect_ratio: 1.0,
             refractive_index: 1.0,
+            count: 0,
+            padding: [0; 3],
+            bubbles: [[0.0; 4]; MAX_BUBBLES],
         };
         Sphere {
-            image: None,
+            center,
+            n_bubbles: bubbles.clamp(1, MAX_BUBBLES),
+            mode,
+            bubbles: Vec::new(),
+            images: ImagePair::default(),
             ended: true,
             shader: None,
             dim,
+            rng: shared_rng(random_seed()),
+        }
+    }
+
+    /// Parameters:
+    /// - center: center (default), random
+    /// - center_x, center_y: the position of the center, relative to the size of the image
+    /// - bubbles: the number of bubbles, from 1 (default) to 8, the others are placed randomly
+    /// - mode: grow (default), shrink
+    pub fn from_params(params: &TransitionParams) -> GameResult<Sphere> {
+        let random = match params.get("center", "center".to_string())?.as_str() {
+            "center" => false,
+            "random" => true,
+            _ => {
+                return Err(GameError::CustomError(
+                    "sphere center must be one of center, random".to_string(),
+                ))
+            }
+        };
+
+        let center = if random {
+            SphereCenter::Random
+        } else {
+            SphereCenter::Fixed(params.get("center_x", 0.5)?, params.get("center_y", 0.5)?)
+        };
+
+        let bubbles = params.get("bubbles", 1)?;
+
+        if bubbles == 0 || bubbles > MAX_BUBBLES {
+            return Err(GameError::CustomError(format!(
+                "sphere bubbles must be between 1 and {}",
+                MAX_BUBBLES
+            )));
+        }
+
+        Ok(Sphere::new(
+            center,
+            bubbles,
+            params.get("mode", SphereMode::Grow)?,
+        ))
+    }
+
+    fn place_bubbles(&mut self) {
+        let mut rng = self.rng.borrow_mut();
+
+        let (x, y) = match self.center {
+            SphereCenter::Random => (rng.gen_range(0.25, 0.75), rng.gen_range(0.25, 0.75)),
+            SphereCenter::Fixed(x, y) => (x, y),
+        };
+
+        self.bubbles.clear();
+
+        if self.n_bubbles == 1 {
+            self.bubbles.push(Bubble {
+                x,
+                y,
+                radius: RADIUS,
+            });
+            return;
+        }
+
+        // smaller bubbles, so together they cover about the same area of a single one, but
+        // they get bigger while they merge
+        let radius = RADIUS / (self.n_bubbles as f32).sqrt();
+
+        self.bubbles.push(Bubble { x, y, radius });
+
+        for _ in 1..self.n_bubbles {
+            self.bubbles.push(Bubble {
+                x: rng.gen_range(0.15, 0.85),
+                y: rng.gen_range(0.15, 0.85),
+                radius: radius * rng.gen_range(0.6, 1.0),
+            });
+        }
+    }
+
+    /// Updates the parameters of the shader for the current rate.
+    fn update_dim(&mut self) {
+        let progress = 1.0 - self.dim.rate;
+
+        self.dim.refractive_index = match self.mode {
+            SphereMode::Grow => 1.0 + progress,
+            SphereMode::Shrink => 2.0 - progress,
+        };
+        self.dim.radius = self.dim.refractive_index / 2.0;
+
+        // a single bubble does not grow, it's only the refraction that changes
+        let scale = match (self.mode, self.bubbles.len()) {
+            (SphereMode::Grow, 1) => 1.0,
+            (SphereMode::Grow, _) => progress,
+            (SphereMode::Shrink, _) => self.dim.rate,
+        };
+
+        self.dim.count = self.bubbles.len() as u32;
+        for (i, bubble) in self.bubbles.iter().enumerate() {
+            self.dim.bubbles[i] = [bubble.x, bubble.y, bubble.radius * scale, 0.0];
         }
     }
 }
 
 impl Transition for Sphere {
+    fn set_rng(&mut self, rng: SharedRng) {
+        self.rng = rng;
+    }
+
     fn draw(&mut self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult<bool> {
         if !self.ended {
+            let param = DrawParam::new().dest(Point2::new(0.0, 0.0));
+
             if self.dim.rate <= 0.0 {
                 self.ended = true;
+
+                if self.mode == SphereMode::Shrink {
+                    if let Some(image) = self.images.image() {
+                        image.draw(canvas, param);
+                    }
+                }
             } else {
-                self.dim.refractive_index = 2.0 - self.dim.rate;
-                self.dim.radius = self.dim.refractive_index / 2.0;
-                match &self.image {
-                    Some(image) => {
-                        let param = DrawParam::new().dest(Point2::new(0.0, 0.0));
-
-                        if let Some(ref shader) = self.shader {
-                            let _lock = canvas.set_shader(&shader);
-                            let shader_params =
-                                graphics::ShaderParamsBuilder::new(&self.dim).build(ctx);
-                            canvas.set_shader_params(&shader_params);
-
-                            image.draw(canvas, param);
-                        }
+                self.update_dim();
+
+                let refracted = match (self.mode, self.images.image()) {
+                    (SphereMode::Grow, Some(image)) => Some((image, param)),
+                    (SphereMode::Shrink, Some(image)) => {
+                        // the new image is under the bubbles, the old one is scaled to its size
+                        image.draw(canvas, param);
+                        self.images.last_image().map(|last_image| {
+                            let scale = Vector2::new(
+                                image.width() as f32 / last_image.width() as f32,
+                                image.height() as f32 / last_image.height() as f32,
+                            );
+                            (last_image, param.scale(scale))
+                        })
+                    }
+                    (_, None) => None,
+                };
+
+                if let Some((image, param)) = refracted {
+                    if let Some(ref shader) = self.shader {
+                        canvas.set_shader(shader);
+                        let shader_params =
+                            graphics::ShaderParamsBuilder::new(&self.dim).build(ctx);
+                        canvas.set_shader_params(&shader_params);
+
+                        image.draw(canvas, param);
                     }
-                    None => {}
                 }
                 self.dim.rate -= 0.01;
             }
@@ -71,22 +259,78 @@ impl Transition for Sphere {
         Ok(!self.ended)
     }
 
-    fn update_image(&mut self, ctx: &mut Context, image: Image) {
-        self.dim.aspect_ratio = image.width() as f32 / image.height() as f32;
-        let shader = graphics::ShaderBuilder::new()
-            //.vertex_path("/basic_150.vert.wgsl")
-            .vertex_path("/simple.vert.wgsl")
-            .fragment_path("/sphere.frag.wgsl")
-            //.fragment_path("/simple.frag.wgsl")
-            .build(ctx)
-            .unwrap();
+    fn prepare(&mut self, ctx: &mut Context, shaders: &mut Shaders) -> GameResult {
+        self.shader = Some(shaders.get(ctx, "/sphere.frag.wgsl")?);
+        Ok(())
+    }
 
-        self.shader = Some(shader);
+    fn skip_to_end(&mut self, _ctx: &mut Context) -> GameResult {
+        self.ended = true;
+        Ok(())
+    }
 
-        //let i = Image::from_bytes(ctx, &image.into_raw()).unwrap();
+    fn update_image(&mut self, ctx: &mut Context, image: Image) {
+        let from = self.images.next_from(ctx, &image);
+        self.set_images(ctx, from, image);
+    }
+
+    fn set_images(&mut self, _ctx: &mut Context, from: Image, to: Image) {
+        self.dim.aspect_ratio = to.width() as f32 / to.height() as f32;
 
-        self.image = Some(image);
+        self.place_bubbles();
+        self.images.set(from, to);
         self.ended = false;
         self.dim.rate = 1.0;
     }
+
+    fn resize(&mut self, _ctx: &mut Context, image: Image) {
+        self.dim.aspect_ratio = image.width() as f32 / image.height() as f32;
+        self.images.resize(image);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_single_bubble_does_not_grow() {
+        let mut sphere = Sphere::new(SphereCenter::Fixed(0.3, 0.6), 1, SphereMode::Grow);
+        sphere.place_bubbles();
+        sphere.dim.rate = 0.5;
+
+        sphere.update_dim();
+
+        assert_eq!(1, sphere.dim.count);
+        assert_eq!(0.3, sphere.dim.bubbles[0][0]);
+        assert_eq!(0.6, sphere.dim.bubbles[0][1]);
+        assert_eq!(RADIUS, sphere.dim.bubbles[0][2]);
+        assert_eq!(1.5, sphere.dim.refractive_index);
+    }
+
+    #[test]
+    fn test_bubbles_grow() {
+        let mut sphere = Sphere::new(SphereCenter::Random, 4, SphereMode::Grow);
+        sphere.place_bubbles();
+        sphere.dim.rate = 0.75;
+
+        sphere.update_dim();
+
+        assert_eq!(4, sphere.dim.count);
+        assert_eq!(RADIUS / 2.0 * 0.25, sphere.dim.bubbles[0][2]);
+        assert!(sphere.dim.bubbles[3][2] > 0.0);
+        assert_eq!(0.0, sphere.dim.bubbles[4][2]);
+    }
+
+    #[test]
+    fn test_shrink() {
+        let mut sphere = Sphere::new(SphereCenter::Fixed(0.5, 0.5), 1, SphereMode::Shrink);
+        sphere.place_bubbles();
+        sphere.dim.rate = 0.25;
+
+        sphere.update_dim();
+
+        assert_eq!(RADIUS * 0.25, sphere.dim.bubbles[0][2]);
+        assert_eq!(1.25, sphere.dim.refractive_index);
+    }
 }
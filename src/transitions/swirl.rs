@@ -0,0 +1,70 @@
+use crevice::std140::AsStd140;
+use ggez::*;
+
+use crate::transitions::params::TransitionParams;
+use crate::transitions::shader_transition::{ShaderDim, ShaderTransition};
+use crate::velocity::*;
+
+// Define the input struct for our shader, public for the type of the transition, but it can be
+// built only here.
+#[derive(AsStd140)]
+pub struct SwirlDim {
+    progress: f32,
+    strength: f32,
+    radius: f32,
+    aspect_ratio: f32,
+    center: mint::Vector2<f32>,
+}
+
+impl ShaderDim for SwirlDim {
+    fn set_progress(&mut self, progress: f32) {
+        self.progress = progress;
+    }
+
+    fn set_aspect_ratio(&mut self, aspect_ratio: f32) {
+        self.aspect_ratio = aspect_ratio;
+    }
+}
+
+/// The old image twists around the center, and the new one untwists, while they cross fade.
+pub type Swirl = ShaderTransition<SwirlDim>;
+
+impl Swirl {
+    /// strength is the rotation at the center in radians, radius is relative to the height of
+    /// the image, the center is relative to the size of the image.
+    pub fn new(strength: f32, radius: f32, center_x: f32, center_y: f32) -> Swirl {
+        let dim = SwirlDim {
+            progress: 0.0,
+            strength,
+            radius: radius.max(0.001),
+            aspect_ratio: 1.0,
+            center: mint::Vector2 {
+                x: center_x,
+                y: center_y,
+            },
+        };
+
+        ShaderTransition::with_shader(
+            "/swirl.frag.wgsl",
+            dim,
+            Box::new(StepsVelocity::new(vec![0.5, 1.5, 0.5])),
+        )
+    }
+
+    /// Parameters:
+    /// - strength: the rotation at the center in the middle of the transition, in radians
+    ///   (default 6)
+    /// - radius: the radius of the swirl, relative to the height of the image (default 0.75)
+    /// - center_x, center_y: the center of the swirl, relative to the size of the image
+    ///   (default 0.5)
+    /// - easing: the easing curve, see Easing (default slow at the start and at the end)
+    pub fn from_params(params: &TransitionParams) -> GameResult<Swirl> {
+        Swirl::new(
+            params.get("strength", 6.0)?,
+            params.get("radius", 0.75)?,
+            params.get("center_x", 0.5)?,
+            params.get("center_y", 0.5)?,
+        )
+        .with_params(params)
+    }
+}
@@ -1,13 +1,120 @@
-use ggez::graphics::{Canvas, DrawParam, Drawable, Image};
+use ggez::graphics::{Canvas, Color, DrawParam, Drawable, Image};
 use ggez::*;
 
 use crate::ggez_utils::Point2;
+use crate::random::SharedRng;
+use crate::sync_timer::PlaybackClock;
+use crate::transitions::shaders::Shaders;
 
 pub trait Transition {
+    /// Called once, before the first image, to build what the transition needs, like shaders,
+    /// so the first transition does not hitch.
+    fn prepare(&mut self, _ctx: &mut Context, _shaders: &mut Shaders) -> GameResult {
+        Ok(())
+    }
+
+    /// Called once, before the first image, with the clock of the slideshow: the transitions that
+    /// depend on time, and not only on the frames, must read it, so they stop when the slideshow
+    /// is paused and follow its speed.
+    fn set_clock(&mut self, _clock: PlaybackClock) {}
+
+    /// Called once, before the first image, with the random generator of the slideshow: the
+    /// transitions that are random must use it, so a run with the same seed is the same.
+    fn set_rng(&mut self, _rng: SharedRng) {}
+
     /// Should return true if the transition is still running.
     fn draw(&mut self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult<bool>;
 
+    /// True if the transition can be drawn at any progress, with draw_at, so it can be reversed.
+    fn reversible(&self) -> bool {
+        false
+    }
+
+    /// Draws the frame of the transition at the given progress, from 0, the old image, to 1, the
+    /// new one, without moving the transition on; only the reversible transitions can.
+    fn draw_at(&mut self, _ctx: &mut Context, _canvas: &mut Canvas, _progress: f32) -> GameResult {
+        Err(GameError::CustomError(
+            "the transition cannot be drawn at a progress".to_string(),
+        ))
+    }
+
     fn update_image(&mut self, ctx: &mut Context, image: Image);
+
+    /// Starts the transition from the image from to the image to, instead of from the image of
+    /// the previous update_image; the combinators use it to give their children both images.
+    /// By default from is ignored, for the transitions that draw the new image over the canvas,
+    /// where the old one already is.
+    fn set_images(&mut self, ctx: &mut Context, _from: Image, to: Image) {
+        self.update_image(ctx, to);
+    }
+
+    /// Called at every update, also while the image is shown after the transition, with the
+    /// shaders of the slideshow, so the transition can build them again; returns true if the
+    /// transition has started again and must be drawn, like when the file of a user defined
+    /// shader changes.
+    fn poll(&mut self, _ctx: &mut Context, _shaders: &mut Shaders) -> bool {
+        false
+    }
+
+    /// Called when a new image interrupts the transition: it must end at once, so the next draw
+    /// returns false, and the next update_image goes from the image of this transition.
+    /// Nothing is drawn, the slideshow draws the image where the transition would have ended.
+    fn skip_to_end(&mut self, ctx: &mut Context) -> GameResult;
+
+    /// Called when the drawable size changes while the transition is running, image is the
+    /// current image fitted to the new size.
+    /// By default the transition is restarted, transitions that do not depend on the geometry of
+    /// the image can override it to just replace the image.
+    fn resize(&mut self, ctx: &mut Context, image: Image) {
+        self.update_image(ctx, image);
+    }
+}
+
+/// How many frames are drawn in this frame, at the given speed, pending keeps the fractions of
+/// frames.
+pub fn frames_to_draw(pending: &mut f32, speed: f32) -> u32 {
+    *pending += speed;
+    let frames = pending.floor();
+    *pending -= frames;
+    frames as u32
+}
+
+/// The old and the new image of a transition that draws both of them.
+/// The first image comes from black. When the window is resized only the new image is replaced,
+/// and the transition goes on: the old one is stretched to the new size, since it's sampled with
+/// texture coordinates, or drawn scaled to the size of the new one.
+#[derive(Default)]
+pub struct ImagePair {
+    image: Option<Image>,
+    last_image: Option<Image>,
+}
+
+impl ImagePair {
+    /// The image where the transition to the given one starts: the new image of the previous
+    /// transition, or black for the first one.
+    pub fn next_from(&self, ctx: &mut Context, image: &Image) -> Image {
+        self.image.clone().unwrap_or_else(|| {
+            Image::from_color(ctx, image.width(), image.height(), Some(Color::BLACK))
+        })
+    }
+
+    pub fn set(&mut self, from: Image, to: Image) {
+        self.last_image = Some(from);
+        self.image = Some(to);
+    }
+
+    /// The new image at the new size of the window.
+    pub fn resize(&mut self, image: Image) {
+        self.image = Some(image);
+    }
+
+    pub fn image(&self) -> Option<&Image> {
+        self.image.as_ref()
+    }
+
+    pub fn last_image(&self) -> Option<&Image> {
+        self.last_image.as_ref()
+    }
 }
 
 pub struct SimpleTransition {
@@ -25,17 +132,12 @@ impl SimpleTransition {
 }
 
 impl Transition for SimpleTransition {
-    fn draw(&mut self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult<bool> {
+    fn draw(&mut self, _ctx: &mut Context, canvas: &mut Canvas) -> GameResult<bool> {
         if !self.ended {
             self.ended = true;
-            match self.image {
-                Some(ref i) => {
-                    let mut canvas = graphics::Canvas::from_frame(ctx, None);
-                    let param = DrawParam::new().dest(Point2::new(0.0, 0.0));
-                    i.draw(&mut canvas, param);
-                    canvas.finish(ctx)?;
-                }
-                None => {}
+            if let Some(ref i) = self.image {
+                let param = DrawParam::new().dest(Point2::new(0.0, 0.0));
+                i.draw(canvas, param);
             }
         }
         Ok(false)
@@ -45,4 +147,29 @@ impl Transition for SimpleTransition {
         self.image = Some(image);
         self.ended = false;
     }
+
+    fn skip_to_end(&mut self, _ctx: &mut Context) -> GameResult {
+        self.ended = true;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frames_to_draw() {
+        let mut pending = 0.0;
+        let slow = (0..4)
+            .map(|_| frames_to_draw(&mut pending, 0.5))
+            .collect::<Vec<_>>();
+        assert_eq!(vec![0, 1, 0, 1], slow);
+
+        let mut pending = 0.0;
+        let fast = (0..4)
+            .map(|_| frames_to_draw(&mut pending, 2.5))
+            .collect::<Vec<_>>();
+        assert_eq!(vec![2, 3, 2, 3], fast);
+    }
 }
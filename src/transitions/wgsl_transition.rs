@@ -0,0 +1,541 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use bytemuck::Zeroable;
+use ggez::graphics::{Canvas, Color, DrawParam, Drawable, Image, Sampler, Text};
+use ggez::*;
+
+use crate::ggez_utils::Point2;
+use crate::transitions::gl_transition::{to_components, Dim, MAX_UNIFORMS};
+use crate::transitions::params::TransitionParams;
+use crate::transitions::shaders::Shaders;
+use crate::transitions::transition::{ImagePair, Transition};
+
+/// how much the progress advances every frame
+const STEP: f32 = 0.01;
+/// how often the file is checked for changes
+const RELOAD_CHECK: Duration = Duration::from_millis(500);
+
+/// The declarations appended to the code of a user transition, WGSL does not care about the
+/// order of declarations, so the line numbers of the errors match the ones of the file.
+const DECLARATIONS: &str = "
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+}
+
+@group(1) @binding(0)
+var t_to: texture_2d<f32>;
+
+@group(1) @binding(1)
+var s_to: sampler;
+
+@group(3) @binding(0)
+var<uniform> uniforms: Uniforms;
+
+@group(3) @binding(1)
+var t_from: texture_2d<f32>;
+
+@group(3) @binding(2)
+var s_from: sampler;
+
+fn from_color(uv: vec2<f32>) -> vec4<f32> {
+    return textureSampleLevel(t_from, s_from, uv, 0.0);
+}
+
+fn to_color(uv: vec2<f32>) -> vec4<f32> {
+    return textureSampleLevel(t_to, s_to, uv, 0.0);
+}
+";
+
+#[derive(Debug, Clone, PartialEq)]
+struct WgslUniform {
+    name: String,
+    /// 1 for f32, otherwise the size of the vector
+    components: usize,
+    default: [f32; 4],
+}
+
+impl WgslUniform {
+    fn wgsl_type(&self) -> String {
+        match self.components {
+            1 => "f32".to_string(),
+            n => format!("vec{}<f32>", n),
+        }
+    }
+
+    /// Parses a value, the components of vectors are separated by ':', like 0.5:0.5.
+    fn parse_value(&self, value: &str) -> Result<[f32; 4], String> {
+        let values = value
+            .split(':')
+            .map(|it| it.trim().parse::<f32>().map_err(|e| e.to_string()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        to_components(self.components, &values)
+    }
+}
+
+/// The header of a user transition, the comments at the start of the file:
+/// ```text
+/// // name: ripple
+/// // uniform amplitude: f32 = 0.05
+/// // uniform center: vec2 = 0.5:0.5
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+struct WgslHeader {
+    name: String,
+    uniforms: Vec<WgslUniform>,
+}
+
+impl WgslHeader {
+    fn parse(source: &str) -> Result<WgslHeader, String> {
+        let mut name = None;
+        let mut uniforms = Vec::new();
+
+        for line in source.lines().map(|it| it.trim()) {
+            let comment = match line.strip_prefix("//") {
+                Some(comment) => comment.trim(),
+                None if line.is_empty() => continue,
+                None => break,
+            };
+
+            if let Some(value) = comment.strip_prefix("name:") {
+                name = Some(value.trim().to_string());
+            } else if let Some(declaration) = comment.strip_prefix("uniform ") {
+                uniforms.push(WgslHeader::parse_uniform(declaration)?);
+            }
+        }
+
+        if uniforms.len() > MAX_UNIFORMS {
+            return Err(format!("more than {} uniforms", MAX_UNIFORMS));
+        }
+
+        Ok(WgslHeader {
+            name: name.ok_or_else(|| "missing '// name:' in the header".to_string())?,
+            uniforms,
+        })
+    }
+
+    /// Parses a declaration like `amplitude: f32 = 0.05`.
+    fn parse_uniform(declaration: &str) -> Result<WgslUniform, String> {
+        let (name, rest) = declaration
+            .split_once(':')
+            .ok_or_else(|| format!("expected name: type in uniform '{}'", declaration))?;
+        let name = name.trim();
+
+        if name == "progress" || name == "ratio" {
+            return Err(format!("uniform {} is reserved", name));
+        }
+
+        let (type_name, default) = match rest.split_once('=') {
+            Some((type_name, default)) => (type_name.trim(), Some(default.trim())),
+            None => (rest.trim(), None),
+        };
+
+        let components = match type_name {
+            "f32" => 1,
+            "vec2" | "vec2<f32>" => 2,
+            "vec3" | "vec3<f32>" => 3,
+            "vec4" | "vec4<f32>" => 4,
+            _ => {
+                return Err(format!(
+                    "unsupported type {} of uniform {}",
+                    type_name, name
+                ))
+            }
+        };
+
+        let mut uniform = WgslUniform {
+            name: name.to_string(),
+            components,
+            default: [0.0; 4],
+        };
+
+        if let Some(default) = default {
+            uniform.default = uniform
+                .parse_value(default)
+                .map_err(|e| format!("invalid default value of uniform {}: {}", name, e))?;
+        }
+
+        Ok(uniform)
+    }
+
+    /// The uniforms struct, every uniform takes a vec4, like in gl_transition::Dim.
+    fn uniforms_struct(&self) -> String {
+        let mut result = String::from("struct Uniforms {\n    progress: f32,\n    ratio: f32,\n");
+
+        for uniform in self.uniforms.iter() {
+            result.push_str(&format!(
+                "    @align(16) {}: {},\n",
+                uniform.name,
+                uniform.wgsl_type()
+            ));
+        }
+
+        result.push_str("}\n");
+        result
+    }
+}
+
+/// Adds the declarations to the code of the transition and validates it, since wgpu panics on
+/// invalid shaders.
+fn to_wgsl(path: &Path, source: &str, header: &WgslHeader) -> Result<String, String> {
+    let wgsl = format!("{}\n{}\n{}", source, DECLARATIONS, header.uniforms_struct());
+    let path = path.to_string_lossy();
+
+    let module = naga::front::wgsl::parse_str(&wgsl)
+        .map_err(|e| e.emit_to_string_with_path(&wgsl, &path))?;
+
+    if !module
+        .entry_points
+        .iter()
+        .any(|it| it.name == "fs_main" && it.stage == naga::ShaderStage::Fragment)
+    {
+        return Err(format!("{}: missing @fragment fn fs_main", path));
+    }
+
+    naga::valid::Validator::new(
+        naga::valid::ValidationFlags::all(),
+        naga::valid::Capabilities::empty(),
+    )
+    .validate(&module)
+    .map_err(|e| e.emit_to_string_with_path(&wgsl, &path))?;
+
+    Ok(wgsl)
+}
+
+/// Finds the file, in the directory, with the given name in the header.
+fn find_transition(dir: &Path, name: &str) -> GameResult<PathBuf> {
+    let entries = dir
+        .read_dir()
+        .map_err(|e| GameError::FilesystemError(format!("{}: {}", dir.display(), e)))?;
+
+    for path in entries.flatten().map(|it| it.path()) {
+        if path.extension().map_or(true, |it| it != "wgsl") {
+            continue;
+        }
+
+        if let Ok(source) = fs::read_to_string(&path) {
+            if matches!(WgslHeader::parse(&source), Ok(header) if header.name == name) {
+                return Ok(path);
+            }
+        }
+    }
+
+    Err(GameError::ResourceNotFound(
+        format!("transition {} in {}", name, dir.display()),
+        Vec::new(),
+    ))
+}
+
+/// A transition written in WGSL by the user, it's reloaded when the file changes, and compile
+/// errors are shown on the screen.
+/// The shader draws the new image, the old one is available with from_color(uv) and the new one
+/// with to_color(uv), the uniforms are in uniforms.progress, uniforms.ratio and
+/// uniforms.<name>.
+pub struct WgslTransition {
+    path: PathBuf,
+    /// the values of the uniforms given as parameters, the others take the default
+    values: HashMap<String, [f32; 4]>,
+    modified: Option<SystemTime>,
+    last_check: Instant,
+    /// the shader must be built again, since the file changed
+    changed: bool,
+    error: Option<String>,
+    shader: Option<graphics::Shader>,
+    images: ImagePair,
+    ended: bool,
+    dim: Dim,
+}
+
+impl WgslTransition {
+    /// Parameters:
+    /// - name: the name, in the header, of the transition
+    /// - dir: the directory with the transitions (default transitions)
+    /// - the uniforms declared in the header, the components of vectors are separated by ':'
+    pub fn from_params(params: &TransitionParams) -> GameResult<WgslTransition> {
+        let name = params.get_opt::<String>("name")?.ok_or_else(|| {
+            GameError::CustomError("the name of the wgsl transition is mandatory".to_string())
+        })?;
+        let dir = params.get("dir", "transitions".to_string())?;
+
+        let path = find_transition(Path::new(&dir), &name)?;
+
+        let source = fs::read_to_string(&path)?;
+        let header = WgslHeader::parse(&source).map_err(GameError::CustomError)?;
+
+        let mut values = HashMap::new();
+
+        for uniform in header.uniforms.iter() {
+            if let Some(value) = params.get_opt::<String>(&uniform.name)? {
+                let parsed = uniform.parse_value(&value).map_err(|e| {
+                    GameError::CustomError(format!(
+                        "Invalid value '{}' for parameter {} of {}: {}",
+                        value, uniform.name, name, e
+                    ))
+                })?;
+                values.insert(uniform.name.clone(), parsed);
+            }
+        }
+
+        Ok(WgslTransition {
+            path,
+            values,
+            modified: None,
+            last_check: Instant::now(),
+            changed: true,
+            error: None,
+            shader: None,
+            images: ImagePair::default(),
+            ended: true,
+            dim: Dim::zeroed(),
+        })
+    }
+
+    /// Checks, at most every RELOAD_CHECK, if the file has been modified.
+    fn check_modified(&mut self) {
+        if !self.changed && self.last_check.elapsed() < RELOAD_CHECK {
+            return;
+        }
+
+        self.last_check = Instant::now();
+
+        let modified = fs::metadata(&self.path).and_then(|it| it.modified()).ok();
+
+        if modified != self.modified {
+            self.modified = modified;
+            self.changed = true;
+        }
+    }
+
+    /// Builds the shader again if the file has changed, returns true if it has.
+    fn reload(&mut self, ctx: &mut Context, shaders: &mut Shaders) -> bool {
+        self.check_modified();
+
+        if !self.changed {
+            return false;
+        }
+
+        self.changed = false;
+
+        match self.build(ctx, shaders) {
+            Ok(shader) => {
+                if self.shader.is_some() {
+                    println!("reloaded {}", self.path.display());
+                }
+                self.shader = Some(shader);
+                self.error = None;
+            }
+            Err(e) => {
+                println!("{}", e);
+                // the old shader is still valid, but it would hide that the file is broken
+                self.shader = None;
+                self.error = Some(e);
+            }
+        }
+
+        true
+    }
+
+    fn build(
+        &mut self,
+        ctx: &mut Context,
+        shaders: &mut Shaders,
+    ) -> Result<graphics::Shader, String> {
+        let source = fs::read_to_string(&self.path)
+            .map_err(|e| format!("{}: {}", self.path.display(), e))?;
+        let header = WgslHeader::parse(&source)?;
+        let wgsl = to_wgsl(&self.path, &source, &header)?;
+
+        let shader = shaders
+            .rebuild_code(ctx, &self.path.to_string_lossy(), &wgsl)
+            .map_err(|e| e.to_string())?;
+
+        self.dim.slots = [[0.0; 4]; MAX_UNIFORMS];
+        for (slot, uniform) in header.uniforms.iter().enumerate() {
+            self.dim.slots[slot] = *self.values.get(&uniform.name).unwrap_or(&uniform.default);
+        }
+
+        Ok(shader)
+    }
+
+    /// Draws the frame at the progress in the parameters of the shader, or the error.
+    fn draw_frame(&self, ctx: &mut Context, canvas: &mut Canvas) {
+        if let (Some(image), Some(last_image)) = (self.images.image(), self.images.last_image()) {
+            let param = DrawParam::new().dest(Point2::new(0.0, 0.0));
+
+            match (&self.shader, &self.error) {
+                (Some(shader), _) => {
+                    canvas.set_shader(shader);
+                    let shader_params = graphics::ShaderParamsBuilder::new(&self.dim)
+                        .images(&[last_image], &[Sampler::linear_clamp()], false)
+                        .build(ctx);
+                    canvas.set_shader_params(&shader_params);
+
+                    image.draw(canvas, param);
+                    canvas.set_default_shader();
+                }
+                (None, Some(error)) => {
+                    image.draw(canvas, param);
+
+                    let mut text = Text::new(error.as_str());
+                    text.set_scale(20.0);
+                    canvas.draw(
+                        &text,
+                        DrawParam::new()
+                            .dest(Point2::new(10.0, 10.0))
+                            .color(Color::RED),
+                    );
+                }
+                (None, None) => image.draw(canvas, param),
+            }
+        }
+    }
+}
+
+impl Transition for WgslTransition {
+    fn draw(&mut self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult<bool> {
+        if self.ended {
+            return Ok(false);
+        }
+
+        self.dim.progress = (self.dim.progress + STEP).min(1.0);
+
+        self.draw_frame(ctx, canvas);
+
+        if self.dim.progress >= 1.0 {
+            self.ended = true;
+        }
+
+        Ok(!self.ended)
+    }
+
+    fn reversible(&self) -> bool {
+        true
+    }
+
+    fn draw_at(&mut self, ctx: &mut Context, canvas: &mut Canvas, progress: f32) -> GameResult {
+        self.dim.progress = progress;
+        self.draw_frame(ctx, canvas);
+        Ok(())
+    }
+
+    /// The shader is built again, in the shared shaders, when the file changes.
+    fn prepare(&mut self, ctx: &mut Context, shaders: &mut Shaders) -> GameResult {
+        self.reload(ctx, shaders);
+        Ok(())
+    }
+
+    fn skip_to_end(&mut self, _ctx: &mut Context) -> GameResult {
+        self.ended = true;
+        Ok(())
+    }
+
+    /// While the image is shown, the transition runs again when the file changes, so the edits
+    /// are seen at once.
+    fn poll(&mut self, ctx: &mut Context, shaders: &mut Shaders) -> bool {
+        if !self.reload(ctx, shaders) || !self.ended || self.images.image().is_none() {
+            return false;
+        }
+
+        self.ended = false;
+        self.dim.progress = 0.0;
+        true
+    }
+
+    fn update_image(&mut self, ctx: &mut Context, image: Image) {
+        let from = self.images.next_from(ctx, &image);
+        self.set_images(ctx, from, image);
+    }
+
+    fn set_images(&mut self, _ctx: &mut Context, from: Image, to: Image) {
+        self.dim.ratio = to.width() as f32 / to.height() as f32;
+        self.images.set(from, to);
+        self.ended = false;
+        self.dim.progress = 0.0;
+    }
+
+    fn resize(&mut self, _ctx: &mut Context, image: Image) {
+        self.dim.ratio = image.width() as f32 / image.height() as f32;
+        self.images.resize(image);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn examples() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("transitions")
+    }
+
+    #[test]
+    fn test_parse_header() {
+        let header = WgslHeader::parse(
+            "// name: ripple\n// uniform amplitude: f32 = 0.05\n//uniform center: vec2 = 0.5\n\nfn a() {}\n// uniform ignored: f32",
+        )
+        .unwrap();
+
+        assert_eq!("ripple", header.name);
+        assert_eq!(
+            vec![
+                WgslUniform {
+                    name: "amplitude".to_string(),
+                    components: 1,
+                    default: [0.05, 0.0, 0.0, 0.0],
+                },
+                WgslUniform {
+                    name: "center".to_string(),
+                    components: 2,
+                    default: [0.5, 0.5, 0.0, 0.0],
+                },
+            ],
+            header.uniforms
+        );
+    }
+
+    #[test]
+    fn test_invalid_header() {
+        assert!(WgslHeader::parse("// uniform amplitude: f32").is_err());
+        assert!(WgslHeader::parse("// name: a\n// uniform progress: f32").is_err());
+        assert!(WgslHeader::parse("// name: a\n// uniform m: mat4x4<f32>").is_err());
+    }
+
+    #[test]
+    fn test_find_transition() {
+        let path = find_transition(&examples(), "ripple").unwrap();
+
+        assert_eq!(
+            Some("ripple.wgsl"),
+            path.file_name().and_then(|it| it.to_str())
+        );
+        assert!(find_transition(&examples(), "missing").is_err());
+    }
+
+    #[test]
+    fn test_examples_are_valid() {
+        for entry in examples().read_dir().unwrap() {
+            let path = entry.unwrap().path();
+            let source = fs::read_to_string(&path).unwrap();
+            let header = WgslHeader::parse(&source).unwrap();
+
+            if let Err(e) = to_wgsl(&path, &source, &header) {
+                panic!("{}", e);
+            }
+        }
+    }
+
+    #[test]
+    fn test_errors_have_the_lines_of_the_file() {
+        let path = Path::new("broken.wgsl");
+        let source = "// name: broken\n\n@fragment\nfn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {\n    return unknown;\n}\n";
+        let header = WgslHeader::parse(source).unwrap();
+
+        let error = to_wgsl(path, source, &header).unwrap_err();
+
+        assert!(error.contains("broken.wgsl:5:"), "{}", error);
+    }
+}
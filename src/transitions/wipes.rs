@@ -0,0 +1,347 @@
+use std::str::FromStr;
+
+use crevice::std140::AsStd140;
+use ggez::graphics::{Canvas, DrawParam, Drawable, Image, Rect};
+use ggez::*;
+
+use crate::ggez_utils::Point2;
+use crate::transitions::params::TransitionParams;
+use crate::transitions::shaders::Shaders;
+use crate::transitions::transition::Transition;
+
+/// how much the progress advances every frame
+const STEP: f32 = 0.01;
+
+/// The slats of the blinds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BlindsOrientation {
+    /// horizontal slats, that open from top to bottom
+    Horizontal,
+    /// vertical slats, that open from left to right
+    Vertical,
+}
+
+impl FromStr for BlindsOrientation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "horizontal" => Ok(BlindsOrientation::Horizontal),
+            "vertical" => Ok(BlindsOrientation::Vertical),
+            _ => Err("expected one of horizontal, vertical".to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IrisShape {
+    Circle,
+    Diamond,
+    Rectangle,
+}
+
+impl FromStr for IrisShape {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "circle" => Ok(IrisShape::Circle),
+            "diamond" => Ok(IrisShape::Diamond),
+            "rectangle" => Ok(IrisShape::Rectangle),
+            _ => Err("expected one of circle, diamond, rectangle".to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IrisMode {
+    /// the new image appears in the center
+    Open,
+    /// the new image appears from the borders
+    Close,
+}
+
+impl FromStr for IrisMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "open" => Ok(IrisMode::Open),
+            "close" => Ok(IrisMode::Close),
+            _ => Err("expected one of open, close".to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WipeKind {
+    /// the orientation and the number of slats
+    Blinds(BlindsOrientation, u32),
+    /// the number of columns, the cells are square, the even ones are revealed first
+    Checkerboard(u32),
+    Iris(IrisShape, IrisMode),
+    /// a hand that turns from twelve o'clock, clockwise if true
+    Clock(bool),
+}
+
+// Define the input struct for our shader.
+#[derive(AsStd140)]
+struct Dim {
+    progress: f32,
+    shape: u32,
+    reverse: u32,
+    aspect_ratio: f32,
+}
+
+/// The parts of the image revealed at the given progress, for the wipes that can be drawn with
+/// rectangles, None for the ones that need the mask shader.
+fn revealed_rects(kind: WipeKind, width: f32, height: f32, progress: f32) -> Option<Vec<Rect>> {
+    let rects = match kind {
+        WipeKind::Blinds(BlindsOrientation::Horizontal, count) => {
+            let slat = height / count as f32;
+            (0..count)
+                .map(|i| Rect::new(0.0, i as f32 * slat, width, slat * progress))
+                .collect()
+        }
+        WipeKind::Blinds(BlindsOrientation::Vertical, count) => {
+            let slat = width / count as f32;
+            (0..count)
+                .map(|i| Rect::new(i as f32 * slat, 0.0, slat * progress, height))
+                .collect()
+        }
+        WipeKind::Checkerboard(columns) => {
+            let size = width / columns as f32;
+            let rows = (height / size).ceil() as u32;
+
+            // the even cells in the first half, the odd ones in the second
+            let even = (progress * 2.0).min(1.0);
+            let odd = (progress * 2.0 - 1.0).max(0.0);
+
+            (0..rows)
+                .flat_map(|row| (0..columns).map(move |column| (row, column)))
+                .map(|(row, column)| {
+                    let fill = if (row + column) % 2 == 0 { even } else { odd };
+                    let y = row as f32 * size;
+                    Rect::new(column as f32 * size, y, size * fill, size.min(height - y))
+                })
+                .collect()
+        }
+        WipeKind::Iris(IrisShape::Rectangle, IrisMode::Open) => {
+            let w = width * progress;
+            let h = height * progress;
+            vec![Rect::new((width - w) / 2.0, (height - h) / 2.0, w, h)]
+        }
+        WipeKind::Iris(IrisShape::Rectangle, IrisMode::Close) => {
+            // the frame around the part of the old image still visible
+            let x = width * progress / 2.0;
+            let y = height * progress / 2.0;
+            vec![
+                Rect::new(0.0, 0.0, width, y),
+                Rect::new(0.0, height - y, width, y),
+                Rect::new(0.0, y, x, height - 2.0 * y),
+                Rect::new(width - x, y, x, height - 2.0 * y),
+            ]
+        }
+        WipeKind::Iris(_, _) | WipeKind::Clock(_) => return None,
+    };
+
+    Some(rects)
+}
+
+/// The classic geometric wipes, the new image is drawn over the old one, only where it's revealed.
+pub struct Wipe {
+    kind: WipeKind,
+    image: Option<Image>,
+    shader: Option<graphics::Shader>,
+    ended: bool,
+    dim: Dim,
+}
+
+impl Wipe {
+    pub fn new(kind: WipeKind) -> Wipe {
+        let (shape, reverse) = match kind {
+            WipeKind::Iris(IrisShape::Diamond, mode) => (1, mode == IrisMode::Close),
+            WipeKind::Iris(_, mode) => (0, mode == IrisMode::Close),
+            WipeKind::Clock(clockwise) => (2, !clockwise),
+            _ => (0, false),
+        };
+
+        Wipe {
+            kind,
+            image: None,
+            shader: None,
+            ended: true,
+            dim: Dim {
+                progress: 0.0,
+                shape,
+                reverse: u32::from(reverse),
+                aspect_ratio: 1.0,
+            },
+        }
+    }
+
+    /// Parameters, by the name of the transition:
+    /// - blinds: orientation: horizontal (default), vertical, count: the number of slats
+    ///   (default 10)
+    /// - checkerboard: columns: the number of columns (default 8)
+    /// - iris: shape: circle (default), diamond, rectangle, mode: open (default), close
+    /// - clockwipe: clockwise: true (default), false
+    pub fn from_params(params: &TransitionParams) -> GameResult<Wipe> {
+        let kind = match params.name() {
+            "blinds" => WipeKind::Blinds(
+                params.get("orientation", BlindsOrientation::Horizontal)?,
+                params.get("count", 10_u32)?.max(1),
+            ),
+            "checkerboard" => WipeKind::Checkerboard(params.get("columns", 8_u32)?.max(1)),
+            "iris" => WipeKind::Iris(
+                params.get("shape", IrisShape::Circle)?,
+                params.get("mode", IrisMode::Open)?,
+            ),
+            "clockwipe" => WipeKind::Clock(params.get("clockwise", true)?),
+            name => return Err(GameError::CustomError(format!("Unknown wipe {}", name))),
+        };
+
+        Ok(Wipe::new(kind))
+    }
+}
+
+impl Transition for Wipe {
+    fn prepare(&mut self, ctx: &mut Context, shaders: &mut Shaders) -> GameResult {
+        // only the wipes that cannot be drawn with rectangles need the shader
+        if revealed_rects(self.kind, 1.0, 1.0, 0.0).is_none() {
+            self.shader = Some(shaders.get(ctx, "/wipe.frag.wgsl")?);
+        }
+
+        Ok(())
+    }
+
+    fn draw(&mut self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult<bool> {
+        if self.ended {
+            return Ok(false);
+        }
+
+        self.dim.progress = (self.dim.progress + STEP).min(1.0);
+
+        if let Some(image) = &self.image {
+            let width = image.width() as f32;
+            let height = image.height() as f32;
+
+            match revealed_rects(self.kind, width, height, self.dim.progress) {
+                Some(rects) => {
+                    for rect in rects.iter().filter(|it| it.w > 0.0 && it.h > 0.0) {
+                        let src = Rect::new(
+                            rect.x / width,
+                            rect.y / height,
+                            rect.w / width,
+                            rect.h / height,
+                        );
+                        image.draw(
+                            canvas,
+                            DrawParam::default()
+                                .src(src)
+                                .dest(Point2::new(rect.x, rect.y)),
+                        );
+                    }
+                }
+                None => {
+                    if let Some(shader) = &self.shader {
+                        canvas.set_shader(shader);
+                        let shader_params =
+                            graphics::ShaderParamsBuilder::new(&self.dim).build(ctx);
+                        canvas.set_shader_params(&shader_params);
+
+                        image.draw(canvas, DrawParam::new().dest(Point2::new(0.0, 0.0)));
+                        canvas.set_default_shader();
+                    }
+                }
+            }
+        }
+
+        if self.dim.progress >= 1.0 {
+            self.ended = true;
+        }
+
+        Ok(!self.ended)
+    }
+
+    fn skip_to_end(&mut self, _ctx: &mut Context) -> GameResult {
+        self.ended = true;
+        Ok(())
+    }
+
+    fn update_image(&mut self, _ctx: &mut Context, image: Image) {
+        self.dim.aspect_ratio = image.width() as f32 / image.height() as f32;
+        self.image = Some(image);
+        self.ended = false;
+        self.dim.progress = 0.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ggez_utils::rects_area;
+
+    use super::*;
+
+    #[test]
+    fn test_blinds() {
+        let rects = revealed_rects(
+            WipeKind::Blinds(BlindsOrientation::Horizontal, 4),
+            100.0,
+            80.0,
+            0.5,
+        )
+        .unwrap();
+
+        assert_eq!(4, rects.len());
+        assert_eq!(Rect::new(0.0, 20.0, 100.0, 10.0), rects[1]);
+    }
+
+    #[test]
+    fn test_checkerboard() {
+        let kind = WipeKind::Checkerboard(4);
+
+        // the even cells first, 4 x 2 cells of 25 pixels
+        let half = revealed_rects(kind, 100.0, 50.0, 0.5).unwrap();
+        assert_eq!(100.0 * 50.0 / 2.0, rects_area(&half));
+        assert_eq!(0.0, half[1].w);
+
+        let all = revealed_rects(kind, 100.0, 50.0, 1.0).unwrap();
+        assert_eq!(100.0 * 50.0, rects_area(&all));
+    }
+
+    #[test]
+    fn test_rectangle_iris() {
+        let open = WipeKind::Iris(IrisShape::Rectangle, IrisMode::Open);
+        let close = WipeKind::Iris(IrisShape::Rectangle, IrisMode::Close);
+
+        assert_eq!(
+            0.0,
+            rects_area(&revealed_rects(open, 100.0, 50.0, 0.0).unwrap())
+        );
+        assert_eq!(
+            5000.0,
+            rects_area(&revealed_rects(open, 100.0, 50.0, 1.0).unwrap())
+        );
+        assert_eq!(
+            0.0,
+            rects_area(&revealed_rects(close, 100.0, 50.0, 0.0).unwrap())
+        );
+        assert_eq!(
+            5000.0,
+            rects_area(&revealed_rects(close, 100.0, 50.0, 1.0).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_masks_need_the_shader() {
+        assert!(revealed_rects(WipeKind::Clock(true), 100.0, 50.0, 0.5).is_none());
+        assert!(revealed_rects(
+            WipeKind::Iris(IrisShape::Circle, IrisMode::Open),
+            1.0,
+            1.0,
+            0.5
+        )
+        .is_none());
+    }
+}
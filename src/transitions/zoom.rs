@@ -0,0 +1,215 @@
+use std::str::FromStr;
+
+use crevice::std140::AsStd140;
+use ggez::graphics::{Canvas, DrawParam, Drawable, Image, Sampler};
+use ggez::*;
+
+use crate::ggez_utils::Point2;
+use crate::transitions::params::TransitionParams;
+use crate::transitions::shaders::Shaders;
+use crate::transitions::transition::{ImagePair, Transition};
+use crate::velocity::*;
+
+/// how much the progress advances every frame, at velocity 1
+const STEP: f32 = 0.01;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ZoomMode {
+    /// the old image grows, with motion blur, while the new one fades in
+    In,
+    /// the old image shrinks, with motion blur, while the new one fades in
+    Out,
+    /// both images are blurred toward the center while they cross fade
+    Cross,
+}
+
+impl ZoomMode {
+    /// The value of the mode in the shader.
+    fn to_uniform(self) -> u32 {
+        match self {
+            ZoomMode::In => 0,
+            ZoomMode::Out => 1,
+            ZoomMode::Cross => 2,
+        }
+    }
+
+    fn default_strength(self) -> f32 {
+        match self {
+            ZoomMode::In | ZoomMode::Out => 1.5,
+            ZoomMode::Cross => 0.4,
+        }
+    }
+}
+
+impl FromStr for ZoomMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "in" => Ok(ZoomMode::In),
+            "out" => Ok(ZoomMode::Out),
+            "cross" => Ok(ZoomMode::Cross),
+            _ => Err("expected one of in, out, cross".to_string()),
+        }
+    }
+}
+
+// Define the input struct for our shader.
+#[derive(AsStd140)]
+struct Dim {
+    progress: f32,
+    strength: f32,
+    aspect_ratio: f32,
+    mode: u32,
+}
+
+pub struct Zoom {
+    images: ImagePair,
+    ended: bool,
+    shader: Option<graphics::Shader>,
+    progress: Progress,
+    velocity: Box<dyn Velocity>,
+    dim: Dim,
+}
+
+impl Zoom {
+    /// strength is how much the old image is scaled at the end, for zoom in and out, or the
+    /// length of the blur, for cross zoom.
+    pub fn new(mode: ZoomMode, strength: f32) -> Zoom {
+        Zoom {
+            images: ImagePair::default(),
+            ended: true,
+            shader: None,
+            progress: Progress::default(),
+            velocity: Zoom::velocity(),
+            dim: Dim {
+                progress: 0.0,
+                strength,
+                aspect_ratio: 1.0,
+                mode: mode.to_uniform(),
+            },
+        }
+    }
+
+    /// Parameters:
+    /// - mode: in, out, cross, the default depends on the name of the transition
+    /// - strength: how much the old image is scaled (default 1.5), or the length of the blur for
+    ///   cross (default 0.4)
+    /// - easing: the easing curve, see Easing (default slow at the start and at the end)
+    pub fn from_params(params: &TransitionParams, default_mode: ZoomMode) -> GameResult<Zoom> {
+        let mode = params.get("mode", default_mode)?;
+        let strength = params.get("strength", mode.default_strength())?;
+
+        let mut zoom = Zoom::new(mode, strength);
+
+        if let Some(easing) = params.get_opt::<Easing>("easing")? {
+            zoom.velocity = Box::new(easing);
+        }
+
+        Ok(zoom)
+    }
+
+    /// Slow at the start and at the end, on average it's about 1.
+    fn velocity() -> Box<dyn Velocity> {
+        Box::new(StepsVelocity::new(vec![0.4, 1.2, 1.6, 1.2, 0.4]))
+    }
+
+    /// Draws the frame at the progress in the parameters of the shader.
+    fn draw_frame(&self, ctx: &mut Context, canvas: &mut Canvas) {
+        if let (Some(image), Some(last_image), Some(shader)) =
+            (self.images.image(), self.images.last_image(), &self.shader)
+        {
+            canvas.set_shader(shader);
+            let shader_params = graphics::ShaderParamsBuilder::new(&self.dim)
+                .images(&[last_image], &[Sampler::linear_clamp()], false)
+                .build(ctx);
+            canvas.set_shader_params(&shader_params);
+
+            image.draw(canvas, DrawParam::new().dest(Point2::new(0.0, 0.0)));
+            canvas.set_default_shader();
+        }
+    }
+}
+
+impl Transition for Zoom {
+    fn prepare(&mut self, ctx: &mut Context, shaders: &mut Shaders) -> GameResult {
+        self.shader = Some(shaders.get(ctx, "/zoom.frag.wgsl")?);
+        Ok(())
+    }
+
+    fn draw(&mut self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult<bool> {
+        if self.ended {
+            return Ok(false);
+        }
+
+        self.progress.advance(self.velocity.as_ref(), STEP);
+        self.dim.progress = self.progress.value;
+
+        self.draw_frame(ctx, canvas);
+
+        if self.progress.ended() {
+            self.ended = true;
+        }
+
+        Ok(!self.ended)
+    }
+
+    fn reversible(&self) -> bool {
+        true
+    }
+
+    fn draw_at(&mut self, ctx: &mut Context, canvas: &mut Canvas, progress: f32) -> GameResult {
+        self.dim.progress = progress;
+        self.draw_frame(ctx, canvas);
+        Ok(())
+    }
+
+    fn skip_to_end(&mut self, _ctx: &mut Context) -> GameResult {
+        self.ended = true;
+        Ok(())
+    }
+
+    fn update_image(&mut self, ctx: &mut Context, image: Image) {
+        let from = self.images.next_from(ctx, &image);
+        self.set_images(ctx, from, image);
+    }
+
+    fn set_images(&mut self, _ctx: &mut Context, from: Image, to: Image) {
+        self.dim.aspect_ratio = to.width() as f32 / to.height() as f32;
+        self.images.set(from, to);
+        self.ended = false;
+        self.progress = Progress::default();
+        self.dim.progress = 0.0;
+    }
+
+    fn resize(&mut self, _ctx: &mut Context, image: Image) {
+        self.dim.aspect_ratio = image.width() as f32 / image.height() as f32;
+        self.images.resize(image);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_eases_in_and_out() {
+        let zoom = Zoom::new(ZoomMode::In, 1.0);
+
+        let mut progress = 0.0;
+        let mut steps = Vec::new();
+        let mut frames = 0;
+
+        while progress < 1.0 {
+            let next = advance(zoom.velocity.as_ref(), progress, STEP);
+            steps.push(next - progress);
+            progress = next;
+            frames += 1;
+        }
+
+        assert_eq!(1.0, progress);
+        assert!(frames > 50 && frames < 200, "{}", frames);
+        // faster in the middle than at the start
+        assert!(steps[frames / 2] > steps[0]);
+    }
+}
@@ -2,6 +2,7 @@ use std::time::Duration;
 
 use separator::Separatable;
 
+#[allow(dead_code)]
 pub fn format_duration(duration: Duration) -> String {
     let secs = duration.as_secs();
     if secs == 0 {
@@ -1,6 +1,16 @@
+use std::f32::consts::PI;
+use std::str::FromStr;
+
 pub trait Velocity {
     /// perc must be between 0. and 1.0
     fn get_velocity(&self, perc: f32) -> f32;
+
+    /// The progress at the given time, both from 0 to 1, for the velocities defined by an easing
+    /// curve, that can go back and forth; None for the ones defined only by the velocity at each
+    /// progress.
+    fn ease(&self, _time: f32) -> Option<f32> {
+        None
+    }
 }
 
 pub struct StepsVelocity {
@@ -31,8 +41,6 @@ impl Velocity for StepsVelocity {
         let velocity_relative_to_right_step =
             velocity_difference * distance_from_right_step / step_width;
 
-        let v = velocity_relative_to_right_step + right_velocity;
-
         /*
         println!("*****************************************", );
         println!("step width={} step_height={}", step_width, step_height);
@@ -42,7 +50,239 @@ impl Velocity for StepsVelocity {
         println!("v={}", v);
         */
 
-        v
+        velocity_relative_to_right_step + right_velocity
+    }
+}
+
+/// The progress, from 0 to 1, after a step at the velocity of the current progress.
+/// It never gets stuck, even where the velocity is 0.
+pub fn advance(velocity: &dyn Velocity, progress: f32, step: f32) -> f32 {
+    (progress + step * velocity.get_velocity(progress).max(0.1)).min(1.0)
+}
+
+/// The time and the progress of a transition, both from 0 to 1.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Progress {
+    pub time: f32,
+    pub value: f32,
+}
+
+impl Progress {
+    /// Advances by a frame: the time by step, the progress following the easing curve of the
+    /// velocity, if it has one, otherwise at the velocity of the current progress.
+    pub fn advance(&mut self, velocity: &dyn Velocity, step: f32) {
+        self.time = (self.time + step).min(1.0);
+
+        match velocity.ease(self.time) {
+            Some(value) => self.value = value,
+            None => {
+                self.value = advance(velocity, self.value, step);
+
+                // the time doesn't matter, but the progress ends with it
+                if self.value >= 1.0 {
+                    self.time = 1.0;
+                }
+            }
+        }
+    }
+
+    pub fn ended(&self) -> bool {
+        self.time >= 1.0
+    }
+}
+
+/// The standard easing curves, see https://easings.net.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Curve {
+    Sine,
+    Quad,
+    Cubic,
+    Expo,
+    /// it goes a bit beyond the ends
+    Back,
+    /// it oscillates around the ends
+    Elastic,
+    /// it bounces on the ends
+    Bounce,
+}
+
+impl Curve {
+    /// The ease in curve, the others are derived from it.
+    fn ease_in(self, t: f32) -> f32 {
+        match self {
+            Curve::Sine => 1.0 - (t * PI / 2.0).cos(),
+            Curve::Quad => t * t,
+            Curve::Cubic => t * t * t,
+            Curve::Expo if t <= 0.0 => 0.0,
+            Curve::Expo => 2.0_f32.powf(10.0 * t - 10.0),
+            Curve::Back => {
+                let c1 = 1.70158;
+                (c1 + 1.0) * t * t * t - c1 * t * t
+            }
+            Curve::Elastic if t <= 0.0 || t >= 1.0 => t,
+            Curve::Elastic => {
+                -(2.0_f32.powf(10.0 * t - 10.0)) * ((t * 10.0 - 10.75) * (2.0 * PI / 3.0)).sin()
+            }
+            Curve::Bounce => 1.0 - bounce_out(1.0 - t),
+        }
+    }
+}
+
+fn bounce_out(t: f32) -> f32 {
+    let n1 = 7.5625;
+    let d1 = 2.75;
+
+    if t < 1.0 / d1 {
+        n1 * t * t
+    } else if t < 2.0 / d1 {
+        let t = t - 1.5 / d1;
+        n1 * t * t + 0.75
+    } else if t < 2.5 / d1 {
+        let t = t - 2.25 / d1;
+        n1 * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / d1;
+        n1 * t * t + 0.984375
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EaseMode {
+    /// slow at the start
+    In,
+    /// slow at the end
+    Out,
+    /// slow at the start and at the end
+    InOut,
+}
+
+/// The easing curves that can be given to the transitions by name:
+/// - linear
+/// - in_sine, out_sine, in_out_sine and the same for quad, cubic, expo, back, elastic, bounce
+/// - bezier:x1:y1:x2:y2, a cubic Bézier curve, like the CSS cubic-bezier
+/// - spring or spring:stiffness, a critically damped spring, stiffer is faster (default 10)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Easing {
+    Linear,
+    Standard(Curve, EaseMode),
+    CubicBezier(f32, f32, f32, f32),
+    Spring(f32),
+}
+
+impl Easing {
+    /// The progress at the given time, it's 0 at the start and 1 at the end.
+    pub fn position(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+
+        match *self {
+            Easing::Linear => t,
+            Easing::Standard(curve, EaseMode::In) => curve.ease_in(t),
+            Easing::Standard(curve, EaseMode::Out) => 1.0 - curve.ease_in(1.0 - t),
+            Easing::Standard(curve, EaseMode::InOut) if t < 0.5 => curve.ease_in(2.0 * t) / 2.0,
+            Easing::Standard(curve, EaseMode::InOut) => 1.0 - curve.ease_in(2.0 - 2.0 * t) / 2.0,
+            Easing::CubicBezier(x1, y1, x2, y2) => cubic_bezier(x1, y1, x2, y2, t),
+            Easing::Spring(stiffness) => {
+                // normalized, so it's exactly 1 at the end
+                let spring = |t: f32| 1.0 - (1.0 + stiffness * t) * (-stiffness * t).exp();
+                spring(t) / spring(1.0)
+            }
+        }
+    }
+}
+
+/// The y of the curve with the given control points, and the ends in (0, 0) and (1, 1), at x.
+fn cubic_bezier(x1: f32, y1: f32, x2: f32, y2: f32, x: f32) -> f32 {
+    let bezier = |a: f32, b: f32, s: f32| {
+        let r = 1.0 - s;
+        3.0 * r * r * s * a + 3.0 * r * s * s * b + s * s * s
+    };
+
+    // the x of the control points are between 0 and 1, so x grows with the parameter
+    let (x1, x2) = (x1.clamp(0.0, 1.0), x2.clamp(0.0, 1.0));
+    let mut low = 0.0;
+    let mut high = 1.0;
+
+    for _ in 0..32 {
+        let middle = (low + high) / 2.0;
+        if bezier(x1, x2, middle) < x {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+
+    bezier(y1, y2, (low + high) / 2.0)
+}
+
+impl Velocity for Easing {
+    /// The slope of the curve, where it reaches perc the first time.
+    fn get_velocity(&self, perc: f32) -> f32 {
+        let mut low = 0.0;
+        let mut high = 1.0;
+
+        for _ in 0..32 {
+            let middle = (low + high) / 2.0;
+            if self.position(middle) < perc {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+
+        let t = ((low + high) / 2.0_f32).clamp(0.001, 0.999);
+        (self.position(t + 0.001) - self.position(t - 0.001)) / 0.002
+    }
+
+    fn ease(&self, time: f32) -> Option<f32> {
+        Some(self.position(time))
+    }
+}
+
+const EASING_ERROR: &str = "expected linear, in_<curve>, out_<curve>, in_out_<curve>, bezier or \
+    spring, the curves are sine, quad, cubic, expo, back, elastic, bounce";
+
+impl FromStr for Easing {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(':');
+        let name = parts.next().unwrap_or_default();
+        let values = parts
+            .map(|it| it.trim().parse::<f32>().map_err(|e| e.to_string()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        match (name, values.as_slice()) {
+            ("linear", []) => return Ok(Easing::Linear),
+            ("bezier", [x1, y1, x2, y2]) => return Ok(Easing::CubicBezier(*x1, *y1, *x2, *y2)),
+            ("bezier", _) => return Err("expected bezier:x1:y1:x2:y2".to_string()),
+            ("spring", []) => return Ok(Easing::Spring(10.0)),
+            ("spring", [stiffness]) if *stiffness > 0.0 => return Ok(Easing::Spring(*stiffness)),
+            ("spring", _) => return Err("expected spring or spring:stiffness".to_string()),
+            (_, []) => {}
+            _ => return Err(format!("unexpected values for {}", name)),
+        }
+
+        let (mode, curve) = [
+            ("in_out_", EaseMode::InOut),
+            ("in_", EaseMode::In),
+            ("out_", EaseMode::Out),
+        ]
+        .iter()
+        .find_map(|(prefix, mode)| name.strip_prefix(prefix).map(|curve| (*mode, curve)))
+        .ok_or_else(|| EASING_ERROR.to_string())?;
+
+        let curve = match curve {
+            "sine" => Curve::Sine,
+            "quad" => Curve::Quad,
+            "cubic" => Curve::Cubic,
+            "expo" => Curve::Expo,
+            "back" => Curve::Back,
+            "elastic" => Curve::Elastic,
+            "bounce" => Curve::Bounce,
+            _ => return Err(EASING_ERROR.to_string()),
+        };
+
+        Ok(Easing::Standard(curve, mode))
     }
 }
 
@@ -55,12 +295,145 @@ mod tests {
         let vel = StepsVelocity::new(vec![1.0, 0.75, 0.5, 0.25, 0.0]); // 0 0.25 0.5 0.75 1
 
         let mut v = vel.get_velocity(0.1);
-        assert_eq!(true, v > 0.75 && v < 1.0);
+        assert!(v > 0.75 && v < 1.0);
 
         v = vel.get_velocity(0.25);
         assert_eq!(0.75, v);
 
         v = vel.get_velocity(0.3);
-        assert_eq!(true, v > 0.5 && v < 0.75);
+        assert!(v > 0.5 && v < 0.75);
+    }
+
+    #[test]
+    fn test_advance() {
+        let vel = StepsVelocity::new(vec![0.0, 2.0]);
+
+        // the velocity at the start is 0, but it moves anyway
+        let progress = advance(&vel, 0.0, 0.1);
+        assert!(progress > 0.0);
+
+        assert_eq!(1.0, advance(&vel, 0.95, 0.1));
+    }
+
+    const CURVES: [&str; 7] = ["sine", "quad", "cubic", "expo", "back", "elastic", "bounce"];
+
+    fn all_easings() -> Vec<Easing> {
+        let mut easings = vec![
+            Easing::Linear,
+            "bezier:0.25:0.1:0.25:1".parse().unwrap(),
+            "spring".parse().unwrap(),
+        ];
+
+        for curve in CURVES {
+            for mode in ["in", "out", "in_out"] {
+                easings.push(format!("{}_{}", mode, curve).parse().unwrap());
+            }
+        }
+
+        easings
+    }
+
+    #[test]
+    fn test_easing_endpoints() {
+        for easing in all_easings() {
+            assert!(easing.position(0.0).abs() < 1e-3, "{:?}", easing);
+            assert!((easing.position(1.0) - 1.0).abs() < 1e-3, "{:?}", easing);
+        }
+    }
+
+    #[test]
+    fn test_easing_monotonicity() {
+        // the others go beyond the ends, or bounce
+        let monotonic = all_easings().into_iter().filter(|it| match it {
+            Easing::Standard(curve, _) => {
+                !matches!(curve, Curve::Back | Curve::Elastic | Curve::Bounce)
+            }
+            _ => true,
+        });
+
+        for easing in monotonic {
+            let mut last = easing.position(0.0);
+
+            for i in 1..=100 {
+                let position = easing.position(i as f32 / 100.0);
+                assert!(position >= last, "{:?} at {}", easing, i);
+                last = position;
+            }
+        }
+    }
+
+    #[test]
+    fn test_easing_modes() {
+        let ease_in: Easing = "in_cubic".parse().unwrap();
+        let ease_out: Easing = "out_cubic".parse().unwrap();
+        let in_out: Easing = "in_out_cubic".parse().unwrap();
+
+        assert!(ease_in.position(0.25) < 0.25);
+        assert!(ease_out.position(0.25) > 0.25);
+        assert!((in_out.position(0.5) - 0.5).abs() < 1e-6);
+        // slow at the start, fast in the middle
+        assert!(ease_in.get_velocity(0.1) < ease_in.get_velocity(0.9));
+    }
+
+    #[test]
+    fn test_overshooting_easings() {
+        let back: Easing = "out_back".parse().unwrap();
+        assert!((0..100).any(|i| back.position(i as f32 / 100.0) > 1.0));
+
+        let bounce: Easing = "out_bounce".parse().unwrap();
+        assert!((0..100).all(|i| bounce.position(i as f32 / 100.0) <= 1.0));
+    }
+
+    #[test]
+    fn test_cubic_bezier() {
+        // the control points on the diagonal are linear
+        let linear = Easing::CubicBezier(0.25, 0.25, 0.75, 0.75);
+        assert!((linear.position(0.3) - 0.3).abs() < 1e-4);
+
+        let ease: Easing = "bezier:0.42:0:1:1".parse().unwrap();
+        assert!(ease.position(0.3) < 0.3);
+    }
+
+    #[test]
+    fn test_parse_easing() {
+        assert_eq!(Ok(Easing::Spring(4.0)), "spring:4".parse());
+        assert_eq!(
+            Ok(Easing::Standard(Curve::Expo, EaseMode::InOut)),
+            "in_out_expo".parse()
+        );
+        assert!("in_linear".parse::<Easing>().is_err());
+        assert!("bezier:1:2".parse::<Easing>().is_err());
+        assert!("spring:-1".parse::<Easing>().is_err());
+        assert!("quad".parse::<Easing>().is_err());
+    }
+
+    #[test]
+    fn test_progress_follows_the_easing() {
+        let back: Easing = "out_back".parse().unwrap();
+        let mut progress = Progress::default();
+        let mut overshoot = false;
+
+        while !progress.ended() {
+            progress.advance(&back, 0.01);
+            overshoot |= progress.value > 1.0;
+        }
+
+        assert!(overshoot);
+        assert!((progress.value - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_progress_ends_with_the_velocity() {
+        let vel = StepsVelocity::new(vec![2.0, 2.0]);
+        let mut progress = Progress::default();
+        let mut frames = 0;
+
+        while !progress.ended() {
+            progress.advance(&vel, 0.1);
+            frames += 1;
+        }
+
+        assert_eq!(1.0, progress.value);
+        assert_eq!(5, frames);
     }
 }
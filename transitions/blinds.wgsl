@@ -0,0 +1,12 @@
+// name: soft_blinds
+// uniform count: f32 = 12.0
+// uniform smoothness: f32 = 0.2
+
+// Vertical blinds that open together, with a soft edge.
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let position = fract(in.uv.x * uniforms.count);
+    let reveal = smoothstep(position, position + uniforms.smoothness, uniforms.progress * (1.0 + uniforms.smoothness));
+
+    return mix(from_color(in.uv), to_color(in.uv), reveal);
+}
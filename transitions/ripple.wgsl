@@ -0,0 +1,24 @@
+// name: ripple
+// uniform amplitude: f32 = 0.05
+// uniform frequency: f32 = 30.0
+// uniform center: vec2 = 0.5
+
+// The new image spreads from the center in waves, that fade out while the transition ends.
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let progress = uniforms.progress;
+    let delta = (in.uv - uniforms.center) * vec2(uniforms.ratio, 1.0);
+    let distance = length(delta);
+
+    var direction = vec2(0.0, 0.0);
+    if distance > 0.0 {
+        direction = delta / distance;
+    }
+
+    let wave = sin(distance * uniforms.frequency - progress * 20.0) * uniforms.amplitude * (1.0 - progress);
+    let offset = direction * wave / vec2(uniforms.ratio, 1.0);
+
+    let edge = smoothstep(progress * 1.5 - 0.2, progress * 1.5, distance);
+
+    return mix(to_color(in.uv + offset), from_color(in.uv + offset), edge);
+}